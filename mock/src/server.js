@@ -45,7 +45,7 @@ app.post("/web/login", (req, res) => {
     req.session.user = email;
     req.session.stoves = {
       12345: readStoveStatusTemplate("12345"),
-      333444: readStoveStatusTemplate("333444"),
+      333444: withOven(readStoveStatusTemplate("333444")),
     };
     res.body = "Found. Redirecting to /web/summary";
     res.redirect("/web/summary");
@@ -62,6 +62,12 @@ function readStoveStatusTemplate(stoveId) {
   return JSON.parse(text);
 }
 
+function withOven(stoveStatus) {
+  stoveStatus.stoveFeatures.bakeMode = true;
+  stoveStatus.sensors.inputBakeTemperature = "21";
+  return stoveStatus;
+}
+
 app.get("/web/logout", (req, res) => {
   global.logoutCount++;
   if (req.session.user) {
@@ -103,6 +109,9 @@ app.post("/api/client/:stoveId/controls", (req, res) => {
     const attributes = Object.keys(req.body);
     const hasOnOff = attributes.includes('onOff');
     const hasHeatingTimesActiveForComfort = attributes.includes('heatingTimesActiveForComfort');
+    const hasEcoMode = attributes.includes('ecoMode');
+    const hasConvectionFan1Active = attributes.includes('convectionFan1Active');
+    const hasConvectionFan2Active = attributes.includes('convectionFan2Active');
     req.session.stoves[stoveId].controls = {
       ...req.session.stoves[stoveId].controls,
       ...(hasOnOff ? { onOff: /true/i.test(req.body.onOff) } : {}),
@@ -110,6 +119,16 @@ app.post("/api/client/:stoveId/controls", (req, res) => {
       ...(req.body.heatingPower ? { heatingPower: Number.parseInt(req.body.heatingPower) } : {}),
       ...(req.body.targetTemperature ? { targetTemperature: req.body.targetTemperature } : {}),
       ...(req.body.setBackTemperature ? { setBackTemperature: req.body.setBackTemperature } : {}),
+      ...(req.body.RoomPowerRequest ? { RoomPowerRequest: Number.parseInt(req.body.RoomPowerRequest) } : {}),
+      ...(req.body.bakeTemperature ? { bakeTemperature: req.body.bakeTemperature } : {}),
+      ...(req.body.temperatureOffset ? { temperatureOffset: req.body.temperatureOffset } : {}),
+      ...(hasEcoMode ? { ecoMode: /true/i.test(req.body.ecoMode) } : {}),
+      ...(hasConvectionFan1Active ? { convectionFan1Active: /true/i.test(req.body.convectionFan1Active) } : {}),
+      ...(req.body.convectionFan1Level ? { convectionFan1Level: Number.parseInt(req.body.convectionFan1Level) } : {}),
+      ...(req.body.convectionFan1Area ? { convectionFan1Area: Number.parseInt(req.body.convectionFan1Area) } : {}),
+      ...(hasConvectionFan2Active ? { convectionFan2Active: /true/i.test(req.body.convectionFan2Active) } : {}),
+      ...(req.body.convectionFan2Level ? { convectionFan2Level: Number.parseInt(req.body.convectionFan2Level) } : {}),
+      ...(req.body.convectionFan2Area ? { convectionFan2Area: Number.parseInt(req.body.convectionFan2Area) } : {}),
       ...(hasHeatingTimesActiveForComfort ? { heatingTimesActiveForComfort: /true/i.test(req.body.heatingTimesActiveForComfort) } : {}),
       ...(req.body.heatingTimeMon1 ? { heatingTimeMon1: req.body.heatingTimeMon1 } : {}),
       ...(req.body.heatingTimeMon2 ? { heatingTimeMon2: req.body.heatingTimeMon2 } : {}),
@@ -63,11 +63,7 @@ impl Middleware for RetryWithAuthMiddleware {
 }
 
 fn is_login_or_logout_request(request: &Request) -> bool {
-    match request.url().path() {
-        "/web/login" => true,
-        "/web/logout" => true,
-        _ => false,
-    }
+    matches!(request.url().path(), "/web/login" | "/web/logout")
 }
 
 fn is_login_redirection(response: &Response) -> bool {
@@ -81,12 +77,7 @@ fn is_login_redirection(response: &Response) -> bool {
         .headers()
         .get("Location")
         .and_then(|header| header.to_str().ok());
-    match location {
-        Some("/web/") => true,
-        Some("/web/login") => true,
-        Some("401") => true,
-        _ => false,
-    }
+    matches!(location, Some("/web/") | Some("/web/login") | Some("401"))
 }
 
 pub(crate) struct OverrideResponseContentTypeHeader {}
@@ -0,0 +1,352 @@
+use std::fmt::{self, Display};
+
+use anyhow::{Result, bail};
+use rika_firenet_openapi::models::{StoveControls, StoveFeatures, StoveStatus};
+use serde::{Deserialize, Serialize};
+
+use crate::error::StoveControlError;
+use crate::model::{ConvectionFan, OperatingMode};
+use crate::model_table::ModelTable;
+
+/// An operation a stove may or may not support.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Capability {
+    OnOff,
+    ManualMode,
+    AutoMode,
+    ComfortMode,
+    FrostProtection,
+    HeatingSchedule,
+    TemperatureOffset,
+    RoomPowerRequest,
+    EcoMode,
+    BakeMode,
+    ConvectionFan(ConvectionFan),
+}
+
+impl Capability {
+    pub const ALL: [Capability; 12] = [
+        Capability::OnOff,
+        Capability::ManualMode,
+        Capability::AutoMode,
+        Capability::ComfortMode,
+        Capability::FrostProtection,
+        Capability::HeatingSchedule,
+        Capability::TemperatureOffset,
+        Capability::RoomPowerRequest,
+        Capability::EcoMode,
+        Capability::BakeMode,
+        Capability::ConvectionFan(ConvectionFan::First),
+        Capability::ConvectionFan(ConvectionFan::Second),
+    ];
+}
+
+impl Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Capability::OnOff => f.write_str("on/off"),
+            Capability::ManualMode => f.write_str("manual mode"),
+            Capability::AutoMode => f.write_str("auto mode"),
+            Capability::ComfortMode => f.write_str("comfort mode"),
+            Capability::FrostProtection => f.write_str("frost protection"),
+            Capability::HeatingSchedule => f.write_str("heating schedule"),
+            Capability::TemperatureOffset => f.write_str("temperature offset"),
+            Capability::RoomPowerRequest => f.write_str("room power request"),
+            Capability::EcoMode => f.write_str("eco mode"),
+            Capability::BakeMode => f.write_str("bake mode"),
+            Capability::ConvectionFan(fan) => fan.fmt(f),
+        }
+    }
+}
+
+/// What a stove is able to do, according to its model, features and reported controls.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StoveCapabilities {
+    pub stove_id: String,
+    /// Manufacturer, e.g. `RIKA`
+    pub oem: String,
+    /// Model name, e.g. `DOMO`
+    pub stove_type: String,
+    /// Model number, informative only as Firenet doesn't document its numbering
+    pub stove_type_number: i32,
+    pub features: StoveFeatures,
+    pub eco_mode_possible: bool,
+    /// Capabilities the stove reports the controls of, the model ones excepted
+    pub available: Vec<Capability>,
+}
+
+impl From<&StoveStatus> for StoveCapabilities {
+    fn from(status: &StoveStatus) -> Self {
+        CapabilityTable::default().capabilities_of(status)
+    }
+}
+
+impl StoveCapabilities {
+    pub fn supports(&self, capability: Capability) -> bool {
+        self.available.contains(&capability)
+    }
+
+    pub fn supported(&self) -> Vec<Capability> {
+        self.available.clone()
+    }
+
+    /// Fails with [`StoveControlError::Unsupported`] when the stove lacks the capability.
+    pub fn ensure(&self, capability: Capability) -> Result<()> {
+        if !self.supports(capability) {
+            bail!(StoveControlError::unsupported(
+                self.stove_id.clone(),
+                capability.to_string()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Fails with [`StoveControlError::Unsupported`] when turning the `current` controls into the
+    /// `requested` ones changes a control the stove lacks the capability of.
+    pub fn ensure_controls(
+        &self,
+        current: &StoveControls,
+        requested: &StoveControls,
+    ) -> Result<()> {
+        for capability in changed_capabilities(current, requested) {
+            self.ensure(capability)?;
+        }
+        Ok(())
+    }
+}
+
+/// Capabilities of the controls differing between `current` and `requested`.
+fn changed_capabilities(current: &StoveControls, requested: &StoveControls) -> Vec<Capability> {
+    let schedule = |controls: &StoveControls| {
+        [
+            controls
+                .heating_times_active_for_comfort
+                .map(|active| active.to_string()),
+            controls.heating_time_mon1.clone(),
+            controls.heating_time_mon2.clone(),
+            controls.heating_time_tue1.clone(),
+            controls.heating_time_tue2.clone(),
+            controls.heating_time_wed1.clone(),
+            controls.heating_time_wed2.clone(),
+            controls.heating_time_thu1.clone(),
+            controls.heating_time_thu2.clone(),
+            controls.heating_time_fri1.clone(),
+            controls.heating_time_fri2.clone(),
+            controls.heating_time_sat1.clone(),
+            controls.heating_time_sat2.clone(),
+            controls.heating_time_sun1.clone(),
+            controls.heating_time_sun2.clone(),
+        ]
+    };
+    let requested_mode = requested
+        .operating_mode
+        .and_then(|mode| OperatingMode::parse(mode.into()).ok())
+        .map(|mode| match mode {
+            OperatingMode::Manual => Capability::ManualMode,
+            OperatingMode::Auto => Capability::AutoMode,
+            OperatingMode::Comfort => Capability::ComfortMode,
+            OperatingMode::Bake => Capability::BakeMode,
+        });
+    let changes = [
+        (current.on_off != requested.on_off).then_some(Capability::OnOff),
+        (current.operating_mode != requested.operating_mode
+            || current.heating_power != requested.heating_power)
+            .then_some(requested_mode)
+            .flatten(),
+        (current.target_temperature != requested.target_temperature
+            || current.set_back_temperature != requested.set_back_temperature)
+            .then_some(Capability::ComfortMode),
+        (current.frost_protection_active != requested.frost_protection_active
+            || current.frost_protection_temperature != requested.frost_protection_temperature)
+            .then_some(Capability::FrostProtection),
+        (schedule(current) != schedule(requested)).then_some(Capability::HeatingSchedule),
+        (current.temperature_offset != requested.temperature_offset)
+            .then_some(Capability::TemperatureOffset),
+        (current.room_power_request != requested.room_power_request)
+            .then_some(Capability::RoomPowerRequest),
+        // turning eco mode off remains possible once the stove stops supporting it
+        (current.eco_mode != requested.eco_mode && requested.eco_mode == Some(true))
+            .then_some(Capability::EcoMode),
+        (current.bake_temperature != requested.bake_temperature).then_some(Capability::BakeMode),
+        ((
+            current.convection_fan1_active,
+            current.convection_fan1_level,
+            current.convection_fan1_area,
+        ) != (
+            requested.convection_fan1_active,
+            requested.convection_fan1_level,
+            requested.convection_fan1_area,
+        ))
+            .then_some(Capability::ConvectionFan(ConvectionFan::First)),
+        ((
+            current.convection_fan2_active,
+            current.convection_fan2_level,
+            current.convection_fan2_area,
+        ) != (
+            requested.convection_fan2_active,
+            requested.convection_fan2_level,
+            requested.convection_fan2_area,
+        ))
+            .then_some(Capability::ConvectionFan(ConvectionFan::Second)),
+    ];
+    let mut capabilities = Vec::new();
+    for capability in changes.into_iter().flatten() {
+        if !capabilities.contains(&capability) {
+            capabilities.push(capability);
+        }
+    }
+    capabilities
+}
+
+/// Whether the stove status carries the features and controls required by the capability.
+fn reports(status: &StoveStatus, capability: Capability) -> bool {
+    let (controls, features) = (&status.controls, &status.stove_features);
+    let power_mode = controls.operating_mode.is_some() && controls.heating_power.is_some();
+    match capability {
+        Capability::OnOff => controls.on_off.is_some(),
+        Capability::ManualMode | Capability::AutoMode => power_mode,
+        Capability::ComfortMode => {
+            controls.operating_mode.is_some()
+                && controls.target_temperature.is_some()
+                && controls.set_back_temperature.is_some()
+        }
+        Capability::FrostProtection => {
+            controls.frost_protection_active.is_some()
+                && controls.frost_protection_temperature.is_some()
+        }
+        Capability::HeatingSchedule => controls.heating_times_active_for_comfort.is_some(),
+        Capability::TemperatureOffset => controls.temperature_offset.is_some(),
+        Capability::RoomPowerRequest => controls.room_power_request.is_some(),
+        Capability::EcoMode => status.sensors.parameter_eco_mode_possible,
+        Capability::BakeMode => features.bake_mode,
+        Capability::ConvectionFan(ConvectionFan::First) => features.multi_air1,
+        Capability::ConvectionFan(ConvectionFan::Second) => features.multi_air2,
+    }
+}
+
+/// Capabilities lacking by manufacturer and model.
+///
+/// Firenet reports some controls even when the model ignores them, declare such models here.
+/// Models missing from the table only depend on their features and reported controls.
+pub type CapabilityTable = ModelTable<Vec<Capability>>;
+
+impl CapabilityTable {
+    pub fn capabilities_of(&self, status: &StoveStatus) -> StoveCapabilities {
+        let lacking = self.get(&status.oem, &status.stove_type);
+        StoveCapabilities {
+            stove_id: status.stove_id.clone(),
+            oem: status.oem.clone(),
+            stove_type: status.stove_type.clone(),
+            stove_type_number: status.sensors.parameter_stove_type_number,
+            features: status.stove_features.clone(),
+            eco_mode_possible: status.sensors.parameter_eco_mode_possible,
+            available: Capability::ALL
+                .into_iter()
+                .filter(|capability| !lacking.contains(capability))
+                .filter(|capability| reports(status, *capability))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rika_firenet_openapi::models::StoveStatus;
+
+    use crate::capabilities::{Capability, CapabilityTable, StoveCapabilities};
+    use crate::error::StoveControlError;
+    use crate::model::ConvectionFan;
+    use crate::tests::mock_status;
+
+    fn stove_status() -> StoveStatus {
+        mock_status("12345")
+    }
+
+    #[test]
+    fn can_build_capabilities_from_status() {
+        let mut status = stove_status();
+        status.stove_features.multi_air1 = true;
+        status.controls.room_power_request = None;
+
+        let capabilities = StoveCapabilities::from(&status);
+
+        assert_eq!(capabilities.oem, "RIKA");
+        assert_eq!(capabilities.stove_type, "DOMO");
+        assert_eq!(capabilities.stove_type_number, 22);
+        assert!(capabilities.supports(Capability::ComfortMode));
+        assert!(capabilities.supports(Capability::ConvectionFan(ConvectionFan::First)));
+        assert!(!capabilities.supports(Capability::ConvectionFan(ConvectionFan::Second)));
+        assert!(!capabilities.supports(Capability::BakeMode));
+        assert!(!capabilities.supports(Capability::EcoMode));
+        assert!(!capabilities.supports(Capability::RoomPowerRequest));
+        assert_eq!(capabilities.supported().len(), 8);
+
+        let stripped = StoveCapabilities::from(&StoveStatus::default());
+        assert!(stripped.supported().is_empty());
+    }
+
+    #[test]
+    fn can_exclude_model_capabilities() {
+        let status = stove_status();
+        let table = CapabilityTable::default().with_model(
+            "RIKA",
+            "DOMO",
+            vec![Capability::TemperatureOffset],
+        );
+
+        assert!(
+            !table
+                .capabilities_of(&status)
+                .supports(Capability::TemperatureOffset)
+        );
+        assert!(table.capabilities_of(&status).supports(Capability::OnOff));
+        assert!(table.get("RIKA", "COMO").is_empty());
+    }
+
+    #[test]
+    fn can_reject_unsupported_operations() {
+        let capabilities = StoveCapabilities::from(&stove_status());
+
+        capabilities.ensure(Capability::ComfortMode).unwrap();
+        let error = capabilities.ensure(Capability::BakeMode).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<StoveControlError>(),
+            Some(&StoveControlError::unsupported("12345", "bake mode"))
+        );
+    }
+
+    #[test]
+    fn can_reject_unsupported_control_changes() {
+        let status = stove_status();
+        let capabilities = StoveCapabilities::from(&status);
+        let current = &status.controls;
+
+        capabilities.ensure_controls(current, current).unwrap();
+        let mut requested = current.clone();
+        requested.target_temperature = Some("22".to_string());
+        requested.eco_mode = Some(false);
+        capabilities.ensure_controls(current, &requested).unwrap();
+
+        requested.convection_fan2_level = Some(3);
+        let error = capabilities
+            .ensure_controls(current, &requested)
+            .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<StoveControlError>(),
+            Some(&StoveControlError::unsupported(
+                "12345",
+                ConvectionFan::Second.to_string()
+            ))
+        );
+
+        let mut requested = current.clone();
+        requested.operating_mode = Some(3);
+        let error = capabilities
+            .ensure_controls(current, &requested)
+            .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<StoveControlError>(),
+            Some(&StoveControlError::unsupported("12345", "bake mode"))
+        );
+    }
+}
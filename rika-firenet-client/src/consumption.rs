@@ -0,0 +1,242 @@
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime};
+
+use rika_firenet_openapi::models::StoveStatus;
+use serde::{Deserialize, Serialize};
+
+pub const HOUR: Duration = Duration::from_secs(60 * 60);
+pub const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+pub const WEEK: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Pellet properties used to turn burnt kilograms into energy and money.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PelletPricing {
+    /// Energy released by one kilogram, about 4.8 kWh for ENplus A1 pellets
+    pub kwh_per_kg: f64,
+    pub bag_price: f64,
+    pub bag_kg: f64,
+}
+
+impl Default for PelletPricing {
+    fn default() -> Self {
+        PelletPricing {
+            kwh_per_kg: 4.8,
+            bag_price: 0.0,
+            bag_kg: 15.0,
+        }
+    }
+}
+
+/// Pellets burnt during a time window.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Consumption {
+    pub kg: f64,
+    pub window: Duration,
+    /// Part of the window covered by snapshots, shorter than the window until enough history is
+    /// recorded
+    pub observed: Duration,
+}
+
+impl Consumption {
+    /// Burn rate over the observed time rather than over the whole window.
+    pub fn kg_per_hour(&self) -> f64 {
+        if self.observed.is_zero() {
+            return 0.0;
+        }
+        self.kg * HOUR.as_secs_f64() / self.observed.as_secs_f64()
+    }
+
+    pub fn energy_kwh(&self, pricing: &PelletPricing) -> f64 {
+        self.kg * pricing.kwh_per_kg
+    }
+
+    pub fn cost(&self, pricing: &PelletPricing) -> f64 {
+        self.kg * pricing.bag_price / pricing.bag_kg
+    }
+}
+
+/// Computes consumption from successive snapshots of `StoveSensors.parameter_feed_rate_total`.
+///
+/// A counter going down is considered reset by a service, the pellets burnt since the reset are
+/// the new counter value. Snapshots older than a week are forgotten.
+///
+/// `StoveSensors.parameter_feed_rate_service` isn't used: it appears to count the kilograms since
+/// the last service, which the total counter already covers, so it gives nothing independent to
+/// calibrate the total against.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConsumptionTracker {
+    last_counter: Option<(SystemTime, i32)>,
+    /// Kilograms burnt between two successive snapshots, with the time of both
+    burnt: VecDeque<(SystemTime, SystemTime, u32)>,
+}
+
+impl ConsumptionTracker {
+    pub fn new() -> Self {
+        ConsumptionTracker::default()
+    }
+
+    /// Records a snapshot taken at the given time, returns the kilograms burnt since the
+    /// previous one.
+    pub fn record(&mut self, at: SystemTime, status: &StoveStatus) -> u32 {
+        self.record_counter(at, status.sensors.parameter_feed_rate_total)
+    }
+
+    pub fn record_counter(&mut self, at: SystemTime, feed_rate_total: i32) -> u32 {
+        let burnt_kg = match self.last_counter {
+            Some((_, previous)) if feed_rate_total >= previous => {
+                (feed_rate_total - previous).unsigned_abs()
+            }
+            Some(_) => feed_rate_total.max(0).unsigned_abs(),
+            None => 0,
+        };
+        if let Some((previous_at, _)) = self.last_counter {
+            self.burnt.push_back((previous_at, at, burnt_kg));
+        }
+        self.last_counter = Some((at, feed_rate_total));
+        while self
+            .burnt
+            .front()
+            .is_some_and(|(_, time, _)| at.duration_since(*time).unwrap_or_default() > WEEK)
+        {
+            self.burnt.pop_front();
+        }
+        burnt_kg
+    }
+
+    /// Time of the latest snapshot.
+    pub fn last_snapshot(&self) -> Option<SystemTime> {
+        self.last_counter.map(|(time, _)| time)
+    }
+
+    /// Pellets burnt during the window ending at the latest snapshot.
+    ///
+    /// An interval between two snapshots starting before the window only counts for the part
+    /// within the window, assuming a steady burn rate over the interval.
+    pub fn consumption(&self, window: Duration) -> Consumption {
+        let Some(last) = self.last_snapshot() else {
+            return Consumption {
+                kg: 0.0,
+                window,
+                observed: Duration::ZERO,
+            };
+        };
+        let start = last.checked_sub(window).unwrap_or(SystemTime::UNIX_EPOCH);
+        let mut kg = 0.0;
+        let mut first_counted = None;
+        for (since, until, burnt) in self.burnt.iter().filter(|(_, until, _)| *until > start) {
+            let interval = until.duration_since(*since).unwrap_or_default();
+            let counted_since = *since.max(&start);
+            let counted = until.duration_since(counted_since).unwrap_or_default();
+            kg += if counted < interval {
+                f64::from(*burnt) * counted.as_secs_f64() / interval.as_secs_f64()
+            } else {
+                f64::from(*burnt)
+            };
+            first_counted.get_or_insert(counted_since);
+        }
+        Consumption {
+            kg,
+            window,
+            observed: first_counted
+                .map(|since| last.duration_since(since).unwrap_or_default())
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn last_hour(&self) -> Consumption {
+        self.consumption(HOUR)
+    }
+
+    pub fn last_day(&self) -> Consumption {
+        self.consumption(DAY)
+    }
+
+    pub fn last_week(&self) -> Consumption {
+        self.consumption(WEEK)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use crate::consumption::{ConsumptionTracker, DAY, HOUR, PelletPricing};
+
+    fn at(hours: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000 + hours * 60 * 60)
+    }
+
+    fn assert_kg(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{actual} kg, expected {expected}"
+        );
+    }
+
+    #[test]
+    fn can_compute_consumption_over_windows() {
+        let mut tracker = ConsumptionTracker::new();
+        assert_eq!(tracker.record_counter(at(0), 368), 0);
+        assert_eq!(tracker.record_counter(at(1), 370), 2);
+        assert_eq!(tracker.record_counter(at(12), 379), 9);
+        assert_eq!(tracker.record_counter(at(24), 381), 2);
+
+        let last_hour = tracker.last_hour();
+        assert_kg(last_hour.kg, 2.0 / 12.0);
+        assert_eq!(last_hour.observed, HOUR);
+        assert_kg(tracker.consumption(13 * HOUR).kg, 2.0 + 9.0 / 11.0);
+        assert_eq!(tracker.last_day().kg, 13.0);
+        assert_eq!(tracker.last_day().kg_per_hour(), 13.0 / 24.0);
+        assert_eq!(tracker.last_week().kg, 13.0);
+    }
+
+    #[test]
+    fn can_handle_counter_resets() {
+        let mut tracker = ConsumptionTracker::new();
+        tracker.record_counter(at(0), 1130);
+        assert_eq!(tracker.record_counter(at(1), 1132), 2);
+        assert_eq!(tracker.record_counter(at(2), 3), 3);
+        assert_eq!(tracker.record_counter(at(3), 4), 1);
+
+        assert_eq!(tracker.last_day().kg, 6.0);
+    }
+
+    #[test]
+    fn can_forget_snapshots_older_than_a_week() {
+        let mut tracker = ConsumptionTracker::new();
+        tracker.record_counter(at(0), 0);
+        tracker.record_counter(at(1), 10);
+        tracker.record_counter(at(24 * 8), 15);
+
+        assert_kg(tracker.last_week().kg, 5.0 * 168.0 / 191.0);
+        assert_eq!(tracker.consumption(10 * DAY).kg, 5.0);
+    }
+
+    #[test]
+    fn can_estimate_energy_and_cost() {
+        let mut tracker = ConsumptionTracker::new();
+        tracker.record_counter(at(0), 0);
+        tracker.record_counter(at(10), 30);
+        let pricing = PelletPricing {
+            bag_price: 6.0,
+            ..Default::default()
+        };
+
+        let consumption = tracker.last_day();
+        assert_eq!(consumption.energy_kwh(&pricing), 144.0);
+        assert_eq!(consumption.cost(&pricing), 12.0);
+    }
+
+    #[test]
+    fn can_compute_the_burn_rate_over_the_observed_time() {
+        let mut tracker = ConsumptionTracker::new();
+        assert_eq!(tracker.last_day().kg_per_hour(), 0.0);
+        tracker.record_counter(at(0), 0);
+        tracker.record_counter(at(10), 30);
+
+        let consumption = tracker.last_day();
+        assert_eq!(consumption.window, DAY);
+        assert_eq!(consumption.observed, 10 * HOUR);
+        assert_eq!(consumption.kg_per_hour(), 3.0);
+    }
+}
@@ -0,0 +1,32 @@
+use std::fmt::{self, Display};
+
+/// Errors raised before sending controls Firenet would silently ignore.
+///
+/// They are wrapped in [`anyhow::Error`], use [`anyhow::Error::downcast_ref`] to match them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StoveControlError {
+    /// The stove lacks the feature required by the operation
+    Unsupported { stove_id: String, operation: String },
+}
+
+impl StoveControlError {
+    pub fn unsupported(stove_id: impl Into<String>, operation: impl Into<String>) -> Self {
+        StoveControlError::Unsupported {
+            stove_id: stove_id.into(),
+            operation: operation.into(),
+        }
+    }
+}
+
+impl Display for StoveControlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoveControlError::Unsupported {
+                stove_id,
+                operation,
+            } => write!(f, "{operation} is unsupported by stove {stove_id}"),
+        }
+    }
+}
+
+impl std::error::Error for StoveControlError {}
@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+
+use serde::{Deserialize, Serialize};
+
+/// Sensor reporting a fault, see `StoveSensors.status_error`, `status_warning` and
+/// `status_service`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FaultSource {
+    Error,
+    Warning,
+    Service,
+}
+
+impl Display for FaultSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FaultSource::Error => f.write_str("error"),
+            FaultSource::Warning => f.write_str("warning"),
+            FaultSource::Service => f.write_str("service"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum FaultSeverity {
+    /// Nothing to do right now, e.g. an upcoming maintenance
+    Info,
+    /// The stove keeps heating but needs attention
+    Warning,
+    /// The stove stopped heating
+    Critical,
+}
+
+/// A fault code reported by Firenet, described when the [`FaultTable`] used to decode it knows
+/// the code.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoveFault {
+    pub source: FaultSource,
+    /// Raw code, e.g. `StoveSensors.status_error`
+    pub code: i32,
+    /// Raw `StoveSensors.status_sub_error` for errors, 0 otherwise
+    pub sub_code: i32,
+    /// From the table when known, otherwise derived from the source alone
+    pub severity: FaultSeverity,
+    pub description: Option<String>,
+    pub suggested_action: Option<String>,
+    /// Document the description comes from
+    pub reference: Option<String>,
+}
+
+/// Meaning of a fault code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FaultDefinition {
+    pub severity: FaultSeverity,
+    pub description: String,
+    pub suggested_action: String,
+    /// Document the description comes from, e.g. a page of the stove manual
+    pub reference: String,
+}
+
+/// Fault definitions by source and code.
+///
+/// The default table is empty: Rika doesn't publish the meaning of the codes, so every code
+/// decodes to its raw numbers. Add a definition only along with the document it comes from.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FaultTable {
+    faults: HashMap<(FaultSource, i32), FaultDefinition>,
+}
+
+impl FaultTable {
+    pub fn with_fault(mut self, source: FaultSource, code: i32, fault: FaultDefinition) -> Self {
+        self.faults.insert((source, code), fault);
+        self
+    }
+
+    pub fn get(&self, source: FaultSource, code: i32) -> Option<&FaultDefinition> {
+        self.faults.get(&(source, code))
+    }
+}
+
+impl StoveFault {
+    /// Looks the code up, unknown codes keep their raw numbers without description.
+    pub fn decode(table: &FaultTable, source: FaultSource, code: i32, sub_code: i32) -> Self {
+        match table.get(source, code) {
+            Some(fault) => StoveFault {
+                source,
+                code,
+                sub_code,
+                severity: fault.severity,
+                description: Some(fault.description.clone()),
+                suggested_action: Some(fault.suggested_action.clone()),
+                reference: Some(fault.reference.clone()),
+            },
+            None => StoveFault {
+                source,
+                code,
+                sub_code,
+                severity: match source {
+                    FaultSource::Error => FaultSeverity::Critical,
+                    FaultSource::Warning => FaultSeverity::Warning,
+                    FaultSource::Service => FaultSeverity::Info,
+                },
+                description: None,
+                suggested_action: None,
+                reference: None,
+            },
+        }
+    }
+
+    /// Whether the code was found in the table used to decode it.
+    pub fn is_known(&self) -> bool {
+        self.description.is_some()
+    }
+}
+
+impl Display for StoveFault {
+    /// e.g. `error 2.1`, followed by the description when known
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.source, self.code)?;
+        if self.sub_code != 0 {
+            write!(f, ".{}", self.sub_code)?;
+        }
+        match &self.description {
+            Some(description) => write!(f, ": {description}"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// How many times an error occurred since the stove was commissioned, see
+/// `StoveSensors.parameter_error_count0` to `parameter_error_count19`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorCount {
+    /// Counter number, e.g. 2 for `parameter_error_count2`
+    ///
+    /// Firenet doesn't document which error each counter counts, so counters carry no
+    /// description: don't assume counter N counts error code N.
+    pub index: usize,
+    pub count: i32,
+}
+
+impl ErrorCount {
+    pub fn new(index: usize, count: i32) -> Self {
+        ErrorCount { index, count }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorCountIncrease {
+    pub index: usize,
+    pub before: i32,
+    pub after: i32,
+}
+
+impl ErrorCountIncrease {
+    pub fn increase(&self) -> i32 {
+        self.after - self.before
+    }
+}
+
+/// Counters which went up between two snapshots of the same stove.
+pub fn increased_error_counts(
+    before: &[ErrorCount; 20],
+    after: &[ErrorCount; 20],
+) -> Vec<ErrorCountIncrease> {
+    before
+        .iter()
+        .zip(after)
+        .filter(|(before, after)| after.count > before.count)
+        .map(|(before, after)| ErrorCountIncrease {
+            index: after.index,
+            before: before.count,
+            after: after.count,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::faults::{
+        ErrorCount, FaultDefinition, FaultSeverity, FaultSource, FaultTable, StoveFault,
+        increased_error_counts,
+    };
+
+    fn ignition_failed() -> FaultTable {
+        FaultTable::default().with_fault(
+            FaultSource::Error,
+            2,
+            FaultDefinition {
+                severity: FaultSeverity::Critical,
+                description: "Ignition failed".to_string(),
+                suggested_action: "Empty the burn pot".to_string(),
+                reference: "Stove manual, page 42".to_string(),
+            },
+        )
+    }
+
+    #[test]
+    fn can_keep_raw_numbers_without_definitions() {
+        let fault = StoveFault::decode(&FaultTable::default(), FaultSource::Error, 2, 1);
+
+        assert!(!fault.is_known());
+        assert_eq!(fault.description, None);
+        assert_eq!(fault.severity, FaultSeverity::Critical);
+        assert_eq!(fault.to_string(), "error 2.1");
+        assert_eq!(
+            StoveFault::decode(&FaultTable::default(), FaultSource::Service, 42, 0).severity,
+            FaultSeverity::Info
+        );
+    }
+
+    #[test]
+    fn can_describe_faults_of_the_table() {
+        let table = ignition_failed();
+        let fault = StoveFault::decode(&table, FaultSource::Error, 2, 1);
+
+        assert!(fault.is_known());
+        assert_eq!(fault.reference.as_deref(), Some("Stove manual, page 42"));
+        assert_eq!(fault.to_string(), "error 2.1: Ignition failed");
+        assert_eq!(
+            StoveFault::decode(&table, FaultSource::Warning, 2, 0).to_string(),
+            "warning 2"
+        );
+    }
+
+    #[test]
+    fn can_deserialize_faults() {
+        let fault = StoveFault::decode(&ignition_failed(), FaultSource::Error, 2, 0);
+        let json = serde_json::to_string(&fault).unwrap();
+
+        assert_eq!(serde_json::from_str::<StoveFault>(&json).unwrap(), fault);
+    }
+
+    #[test]
+    fn can_report_increased_error_counts() {
+        let before: [ErrorCount; 20] = std::array::from_fn(|index| ErrorCount::new(index, 1));
+        let mut after = before;
+        after[2] = ErrorCount::new(2, 4);
+        after[17] = ErrorCount::new(17, 2);
+        after[5] = ErrorCount::new(5, 0);
+
+        let increases = increased_error_counts(&before, &after);
+
+        assert_eq!(increases.len(), 2);
+        assert_eq!(increases[0].index, 2);
+        assert_eq!(increases[0].increase(), 3);
+        assert_eq!(increases[1].index, 17);
+        assert_eq!(increases[1].increase(), 1);
+    }
+}
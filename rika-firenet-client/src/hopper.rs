@@ -0,0 +1,167 @@
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use rika_firenet_openapi::models::StoveStatus;
+use serde::{Deserialize, Serialize};
+
+use crate::consumption::ConsumptionTracker;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum HopperEvent {
+    /// The estimated level went below the low level threshold
+    LowLevel { level_kg: f64 },
+}
+
+/// Estimates the pellets left in the hopper, stoves don't have a level sensor.
+///
+/// The level is reduced by the consumption computed from `StoveSensors.parameter_feed_rate_total`
+/// and raised by refills. Serialize it or use [`HopperEstimator::save`] to keep it across
+/// restarts.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HopperEstimator {
+    capacity_kg: f64,
+    low_level_kg: f64,
+    level_kg: f64,
+    low_level_reported: bool,
+    tracker: ConsumptionTracker,
+}
+
+impl HopperEstimator {
+    /// Starts with an empty hopper, call [`HopperEstimator::refill`] with the current content.
+    pub fn new(capacity_kg: f64, low_level_kg: f64) -> Self {
+        HopperEstimator {
+            capacity_kg,
+            low_level_kg,
+            level_kg: 0.0,
+            low_level_reported: true,
+            tracker: ConsumptionTracker::new(),
+        }
+    }
+
+    pub fn level_kg(&self) -> f64 {
+        self.level_kg
+    }
+
+    pub fn capacity_kg(&self) -> f64 {
+        self.capacity_kg
+    }
+
+    pub fn tracker(&self) -> &ConsumptionTracker {
+        &self.tracker
+    }
+
+    /// Adds pellets, the level can't go over the hopper capacity.
+    pub fn refill(&mut self, kg: f64) {
+        self.level_kg = (self.level_kg + kg).min(self.capacity_kg);
+        self.low_level_reported = self.level_kg <= self.low_level_kg;
+    }
+
+    /// Records a snapshot, reports the low level once until the next refill.
+    pub fn record(&mut self, at: SystemTime, status: &StoveStatus) -> Option<HopperEvent> {
+        self.record_counter(at, status.sensors.parameter_feed_rate_total)
+    }
+
+    pub fn record_counter(&mut self, at: SystemTime, feed_rate_total: i32) -> Option<HopperEvent> {
+        let burnt_kg = self.tracker.record_counter(at, feed_rate_total);
+        self.level_kg = (self.level_kg - f64::from(burnt_kg)).max(0.0);
+        if self.level_kg <= self.low_level_kg && !self.low_level_reported {
+            self.low_level_reported = true;
+            return Some(HopperEvent::LowLevel {
+                level_kg: self.level_kg,
+            });
+        }
+        None
+    }
+
+    /// When the hopper will run empty at the burn rate observed during the last day, `None` when
+    /// the stove didn't burn anything. With less than a day of history the rate only covers the
+    /// recorded snapshots.
+    pub fn predicted_empty_at(&self) -> Option<SystemTime> {
+        let kg_per_hour = self.tracker.last_day().kg_per_hour();
+        let last_snapshot = self.tracker.last_snapshot()?;
+        if kg_per_hour <= 0.0 {
+            return None;
+        }
+        let hours_left = self.level_kg / kg_per_hour;
+        Some(last_snapshot + Duration::from_secs_f64(hours_left * 60.0 * 60.0))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Can't read hopper state from {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Can't parse hopper state from {}", path.display()))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        fs::write(path, serde_json::to_string(self)?)
+            .with_context(|| format!("Can't write hopper state to {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use crate::hopper::{HopperEstimator, HopperEvent};
+
+    fn at(hours: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000 + hours * 60 * 60)
+    }
+
+    #[test]
+    fn can_estimate_level_and_report_low_level_once() {
+        let mut hopper = HopperEstimator::new(40.0, 10.0);
+        hopper.refill(25.0);
+        assert_eq!(hopper.record_counter(at(0), 100), None);
+        assert_eq!(hopper.record_counter(at(6), 110), None);
+        assert_eq!(hopper.level_kg(), 15.0);
+
+        assert_eq!(
+            hopper.record_counter(at(12), 116),
+            Some(HopperEvent::LowLevel { level_kg: 9.0 })
+        );
+        assert_eq!(hopper.record_counter(at(18), 120), None);
+        assert_eq!(hopper.level_kg(), 5.0);
+
+        hopper.refill(50.0);
+        assert_eq!(hopper.level_kg(), 40.0);
+    }
+
+    #[test]
+    fn can_predict_when_the_hopper_runs_empty() {
+        let mut hopper = HopperEstimator::new(40.0, 10.0);
+        hopper.refill(30.0);
+        assert_eq!(hopper.predicted_empty_at(), None);
+
+        hopper.record_counter(at(0), 100);
+        hopper.record_counter(at(10), 110);
+
+        assert_eq!(hopper.predicted_empty_at(), Some(at(30)));
+
+        // 28 kg left, 12 kg burnt during the last 24 hours
+        hopper.refill(10.0);
+        hopper.record_counter(at(24), 112);
+        assert_eq!(hopper.predicted_empty_at(), Some(at(24 + 56)));
+    }
+
+    #[test]
+    fn can_persist_state() {
+        let path = std::env::temp_dir().join(format!("hopper-{}.json", std::process::id()));
+        let mut hopper = HopperEstimator::new(40.0, 10.0);
+        hopper.refill(30.0);
+        hopper.record_counter(at(0), 100);
+        hopper.record_counter(at(10), 110);
+
+        hopper.save(&path).unwrap();
+        let restored = HopperEstimator::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(restored, hopper);
+        assert_eq!(restored.level_kg(), 20.0);
+    }
+}
@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+
+use rika_firenet_openapi::models::StoveStatus;
+use serde::{Deserialize, Serialize};
+
+use crate::model_table::ModelTable;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FirmwareComponent {
+    MainBoard,
+    Tft,
+    WiFi,
+}
+
+impl FirmwareComponent {
+    pub const ALL: [FirmwareComponent; 3] = [
+        FirmwareComponent::MainBoard,
+        FirmwareComponent::Tft,
+        FirmwareComponent::WiFi,
+    ];
+}
+
+impl Display for FirmwareComponent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FirmwareComponent::MainBoard => f.write_str("main board"),
+            FirmwareComponent::Tft => f.write_str("TFT display"),
+            FirmwareComponent::WiFi => f.write_str("WiFi module"),
+        }
+    }
+}
+
+/// Firmware version, ordered by main then sub version.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct FirmwareVersion {
+    pub main: i32,
+    pub sub: i32,
+}
+
+impl FirmwareVersion {
+    pub const fn new(main: i32, sub: i32) -> Self {
+        FirmwareVersion { main, sub }
+    }
+}
+
+impl Display for FirmwareVersion {
+    /// e.g. `228.52003`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.main, self.sub)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComponentFirmware {
+    pub version: FirmwareVersion,
+    pub boot_loader: i32,
+}
+
+impl Display for ComponentFirmware {
+    /// e.g. `228.52003 (bootloader 160)`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (bootloader {})", self.version, self.boot_loader)
+    }
+}
+
+/// Hardware and firmware description of a stove unit.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoveInventory {
+    pub stove_id: String,
+    pub oem: String,
+    /// Model name, e.g. `DOMO`
+    pub stove_type: String,
+    pub stove_type_number: i32,
+    pub fabrication_number: i32,
+    pub main_board: ComponentFirmware,
+    pub tft: ComponentFirmware,
+    pub wifi: ComponentFirmware,
+}
+
+impl From<&StoveStatus> for StoveInventory {
+    fn from(status: &StoveStatus) -> Self {
+        let sensors = &status.sensors;
+        StoveInventory {
+            stove_id: status.stove_id.clone(),
+            oem: status.oem.clone(),
+            stove_type: status.stove_type.clone(),
+            stove_type_number: sensors.parameter_stove_type_number,
+            fabrication_number: sensors.parameter_fabrication_number,
+            main_board: ComponentFirmware {
+                version: FirmwareVersion::new(
+                    sensors.parameter_version_main_board,
+                    sensors.parameter_version_main_board_sub,
+                ),
+                boot_loader: sensors.parameter_version_main_board_boot_loader,
+            },
+            tft: ComponentFirmware {
+                version: FirmwareVersion::new(
+                    sensors.parameter_version_tft,
+                    sensors.parameter_version_tft_sub,
+                ),
+                boot_loader: sensors.parameter_version_tft_boot_loader,
+            },
+            wifi: ComponentFirmware {
+                version: FirmwareVersion::new(
+                    sensors.parameter_version_wi_fi,
+                    sensors.parameter_version_wi_fi_sub,
+                ),
+                boot_loader: sensors.parameter_version_wi_fi_boot_loader,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutdatedFirmware {
+    pub component: FirmwareComponent,
+    pub installed: FirmwareVersion,
+    pub minimum: FirmwareVersion,
+}
+
+impl Display for OutdatedFirmware {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} firmware {} is older than {}",
+            self.component, self.installed, self.minimum
+        )
+    }
+}
+
+impl StoveInventory {
+    pub fn firmware(&self, component: FirmwareComponent) -> &ComponentFirmware {
+        match component {
+            FirmwareComponent::MainBoard => &self.main_board,
+            FirmwareComponent::Tft => &self.tft,
+            FirmwareComponent::WiFi => &self.wifi,
+        }
+    }
+
+    /// Components running a firmware older than required for this model.
+    pub fn outdated_firmware(&self, table: &MinimumFirmwareTable) -> Vec<OutdatedFirmware> {
+        let minimums = table.get(&self.oem, &self.stove_type);
+        FirmwareComponent::ALL
+            .into_iter()
+            .filter_map(|component| {
+                let installed = self.firmware(component).version;
+                minimums
+                    .get(&component)
+                    .filter(|minimum| installed < **minimum)
+                    .map(|minimum| OutdatedFirmware {
+                        component,
+                        installed,
+                        minimum: *minimum,
+                    })
+            })
+            .collect()
+    }
+}
+
+/// Minimum firmware versions by component, components missing aren't checked.
+pub type MinimumFirmware = HashMap<FirmwareComponent, FirmwareVersion>;
+
+/// Minimum firmware versions by manufacturer and model, models missing from the table use the
+/// default minimums.
+pub type MinimumFirmwareTable = ModelTable<MinimumFirmware>;
+
+#[cfg(test)]
+mod tests {
+    use crate::inventory::{
+        FirmwareComponent, FirmwareVersion, MinimumFirmware, MinimumFirmwareTable,
+        OutdatedFirmware, StoveInventory,
+    };
+    use crate::tests::mock_status;
+
+    fn mock_inventory() -> StoveInventory {
+        StoveInventory::from(&mock_status("__stove_id__"))
+    }
+
+    #[test]
+    fn can_describe_a_stove_unit() {
+        let inventory = mock_inventory();
+
+        assert_eq!(inventory.stove_type, "DOMO");
+        assert_eq!(inventory.stove_type_number, 22);
+        assert_eq!(inventory.fabrication_number, 1);
+        assert_eq!(
+            inventory.main_board.to_string(),
+            "228.52003 (bootloader 160)"
+        );
+        assert_eq!(inventory.tft.to_string(), "228.50002 (bootloader 160)");
+        assert_eq!(inventory.wifi.to_string(), "112.13301 (bootloader 101)");
+    }
+
+    #[test]
+    fn can_find_outdated_firmware() {
+        let inventory = mock_inventory();
+        let table = MinimumFirmwareTable::new(MinimumFirmware::from([(
+            FirmwareComponent::MainBoard,
+            FirmwareVersion::new(200, 0),
+        )]))
+        .with_model(
+            "RIKA",
+            "DOMO",
+            MinimumFirmware::from([
+                (
+                    FirmwareComponent::MainBoard,
+                    FirmwareVersion::new(228, 52003),
+                ),
+                (FirmwareComponent::Tft, FirmwareVersion::new(228, 50010)),
+                (FirmwareComponent::WiFi, FirmwareVersion::new(112, 13301)),
+            ]),
+        );
+
+        assert_eq!(
+            inventory.outdated_firmware(&table),
+            vec![OutdatedFirmware {
+                component: FirmwareComponent::Tft,
+                installed: FirmwareVersion::new(228, 50002),
+                minimum: FirmwareVersion::new(228, 50010),
+            }]
+        );
+        assert_eq!(
+            inventory.outdated_firmware(&table)[0].to_string(),
+            "TFT display firmware 228.50002 is older than 228.50010"
+        );
+        assert_eq!(
+            inventory.outdated_firmware(&MinimumFirmwareTable::default()),
+            vec![]
+        );
+    }
+}
@@ -1,13 +1,20 @@
 use std::sync::Arc;
 
 use crate::api_internals::OverrideResponseContentTypeHeader;
-use anyhow::{Result, ensure};
+use anyhow::{Result, bail, ensure};
 use api_internals::RetryWithAuthMiddleware;
 use async_trait::async_trait;
 use bon::bon;
+use capabilities::{Capability, CapabilityTable, StoveCapabilities};
+use error::StoveControlError;
+use faults::{ErrorCount, FaultSource, FaultTable, StoveFault};
 use lazy_static::lazy_static;
-use log::debug;
-use model::{HeatingSchedule, OperatingMode, StatusDetail};
+use limits::{LimitsTable, StoveLimits};
+use log::{debug, warn};
+use model::{
+    BakeModeInputs, ConvectionFan, ConvectionFanSettings, DailySchedule, HeatingSchedule,
+    OperatingMode, RoomPowerRequest, StatusDetail, StatusInterpretation, Weekday,
+};
 use nipper::Document;
 use regex::Regex;
 use reqwest::{Client, redirect::Policy};
@@ -18,10 +25,21 @@ use rika_firenet_openapi::apis::{
     configuration::Configuration,
     stoves_api::{StoveControlsParams, StoveStatusParams},
 };
-pub use rika_firenet_openapi::models::{StoveControls, StoveStatus};
+pub use rika_firenet_openapi::models::{StoveControls, StoveStatus, Temperature};
 
 mod api_internals;
+pub mod capabilities;
+pub mod consumption;
+pub mod error;
+pub mod faults;
+pub mod hopper;
+pub mod inventory;
+pub mod limits;
+pub mod maintenance;
 pub mod model;
+pub mod model_table;
+pub mod runtime;
+pub mod schedule_compiler;
 
 const API_BASE_URL: &str = "https://www.rika-firenet.com";
 const FIREFOX_USER_AGENT: &str =
@@ -32,6 +50,10 @@ lazy_static! {
             Regex::new("(?P<firstStartHH>\\d{2})(?P<firstStartMM>\\d{2})(?P<firstEndHH>\\d{2})(?P<firstEndMM>\\d{2})(?P<secondStartHH>\\d{2})(?P<secondStartMM>\\d{2})(?P<secondndHH>\\d{2})(?P<secondEndMM>\\d{2})").unwrap();
 }
 
+/// Stove operations. Operations added after the first release have a default implementation,
+/// so that existing implementations keep compiling: controls fail with
+/// [`StoveControlError::Unsupported`] while queries and schedule edits rely on [`Self::status`]
+/// and [`Self::enable_schedule`].
 #[async_trait]
 pub trait RikaFirenet {
     async fn list_stoves(&self) -> Result<Vec<String>>;
@@ -54,14 +76,71 @@ pub trait RikaFirenet {
         frost_protection_temperature: u8,
     ) -> Result<()>;
     async fn disable_frost_protection(&self, stove_id: String) -> Result<()>;
+    async fn set_bake_mode(&self, stove_id: String, _bake_temperature: u16) -> Result<()> {
+        unsupported(stove_id, Capability::BakeMode)
+    }
+    async fn set_temperature_offset(&self, stove_id: String, _offset: f32) -> Result<()> {
+        unsupported(stove_id, Capability::TemperatureOffset)
+    }
+    async fn set_room_power_request(
+        &self,
+        stove_id: String,
+        _request: RoomPowerRequest,
+    ) -> Result<()> {
+        unsupported(stove_id, Capability::RoomPowerRequest)
+    }
+    async fn set_eco_mode(&self, stove_id: String, _enabled: bool) -> Result<()> {
+        unsupported(stove_id, Capability::EcoMode)
+    }
+    async fn set_convection_fan(
+        &self,
+        stove_id: String,
+        fan: ConvectionFan,
+        _settings: ConvectionFanSettings,
+    ) -> Result<()> {
+        unsupported(stove_id, Capability::ConvectionFan(fan))
+    }
     async fn enable_schedule(&self, stove_id: String, schedule: HeatingSchedule) -> Result<()>;
+    async fn disable_schedule(&self, stove_id: String) -> Result<()> {
+        unsupported(stove_id, Capability::HeatingSchedule)
+    }
+    async fn get_schedule(&self, stove_id: String) -> Result<HeatingSchedule> {
+        Ok(self.status(stove_id).await?.get_heating_schedule())
+    }
+    async fn update_day(
+        &self,
+        stove_id: String,
+        weekday: Weekday,
+        day_schedule: DailySchedule,
+    ) -> Result<()> {
+        let mut schedule = self.get_schedule(stove_id.clone()).await?;
+        *schedule.day_mut(weekday) = day_schedule;
+        self.enable_schedule(stove_id, schedule).await
+    }
+    async fn copy_day(&self, stove_id: String, from: Weekday, to: Weekday) -> Result<()> {
+        let mut schedule = self.get_schedule(stove_id.clone()).await?;
+        *schedule.day_mut(to) = schedule.day(from).clone();
+        self.enable_schedule(stove_id, schedule).await
+    }
+    async fn capabilities(&self, stove_id: String) -> Result<StoveCapabilities> {
+        Ok(StoveCapabilities::from(&self.status(stove_id).await?))
+    }
 
     async fn logout(&self) -> Result<()>;
 }
 
+fn unsupported(stove_id: String, capability: Capability) -> Result<()> {
+    bail!(StoveControlError::unsupported(
+        stove_id,
+        capability.to_string()
+    ))
+}
+
 pub struct RikaFirenetClient {
     auth_api: Arc<dyn AuthApi>,
     stoves_api: Arc<dyn StovesApi>,
+    limits: LimitsTable,
+    capabilities: CapabilityTable,
 }
 
 #[bon]
@@ -72,6 +151,8 @@ impl RikaFirenetClient {
         #[builder(finish_fn)] password: String,
         base_url: Option<String>,
         reqwest_middleware: Option<Arc<dyn Middleware>>,
+        limits: Option<LimitsTable>,
+        capabilities: Option<CapabilityTable>,
     ) -> Self {
         let inner_client = Client::builder()
             .cookie_store(true)
@@ -119,10 +200,31 @@ impl RikaFirenetClient {
                 }
                 .into(),
             )),
+            limits: limits.unwrap_or_default(),
+            capabilities: capabilities.unwrap_or_default(),
         }
     }
 }
 
+impl RikaFirenetClient {
+    /// Fetches the stove status, failing when the stove lacks the capability.
+    async fn status_supporting(
+        &self,
+        stove_id: String,
+        capability: Capability,
+    ) -> Result<StoveStatus> {
+        let status = self.status(stove_id).await?;
+        self.capabilities
+            .capabilities_of(&status)
+            .ensure(capability)?;
+        Ok(status)
+    }
+
+    fn limits_of(&self, status: &StoveStatus) -> &StoveLimits {
+        self.limits.get(&status.oem, &status.stove_type)
+    }
+}
+
 #[async_trait]
 impl RikaFirenet for RikaFirenetClient {
     async fn list_stoves(&self) -> Result<Vec<String>> {
@@ -136,14 +238,15 @@ impl RikaFirenet for RikaFirenetClient {
     async fn status(&self, stove_id: String) -> Result<StoveStatus> {
         Ok(self
             .stoves_api
-            .stove_status(StoveStatusParams {
-                stove_id: stove_id.into(),
-            })
+            .stove_status(StoveStatusParams { stove_id })
             .await?)
     }
 
     async fn restore_controls(&self, stove_id: String, controls: StoveControls) -> Result<()> {
         let current_status = self.status(stove_id).await?;
+        self.capabilities
+            .capabilities_of(&current_status)
+            .ensure_controls(&current_status.controls, &controls)?;
         let restore_status = StoveStatus {
             controls,
             ..current_status
@@ -155,7 +258,10 @@ impl RikaFirenet for RikaFirenetClient {
     async fn turn_on(&self, stove_id: String) -> Result<()> {
         let params = StoveControlsParams {
             on_off: Some(true),
-            ..self.status(stove_id).await?.into_stove_controls()
+            ..self
+                .status_supporting(stove_id, Capability::OnOff)
+                .await?
+                .into_stove_controls()
         };
         Ok(self.stoves_api.stove_controls(params).await?)
     }
@@ -163,33 +269,46 @@ impl RikaFirenet for RikaFirenetClient {
     async fn turn_off(&self, stove_id: String) -> Result<()> {
         let params = StoveControlsParams {
             on_off: Some(false),
-            ..self.status(stove_id).await?.into_stove_controls()
+            ..self
+                .status_supporting(stove_id, Capability::OnOff)
+                .await?
+                .into_stove_controls()
         };
         Ok(self.stoves_api.stove_controls(params).await?)
     }
 
     async fn set_manual_mode(&self, stove_id: String, heating_power_percent: u8) -> Result<()> {
-        ensure!(
-            (0..100).contains(&heating_power_percent),
-            "Heating power must be 0 <= power <= 100 but it was {heating_power_percent}"
-        );
+        let status = self
+            .status_supporting(stove_id, Capability::ManualMode)
+            .await?;
+        self.limits_of(&status).heating_power.ensure(
+            "Heating power",
+            "power",
+            "",
+            heating_power_percent.into(),
+        )?;
         let params = StoveControlsParams {
             operating_mode: Some(OperatingMode::Manual.into()),
-            heating_power: Some(heating_power_percent.into()),
-            ..self.status(stove_id).await?.into_stove_controls()
+            heating_power: Some(heating_power_percent),
+            ..status.into_stove_controls()
         };
         Ok(self.stoves_api.stove_controls(params).await?)
     }
 
     async fn set_auto_mode(&self, stove_id: String, heating_power_percent: u8) -> Result<()> {
-        ensure!(
-            (0..100).contains(&heating_power_percent),
-            "Heating power must be 0 <= power <= 100 but it was {heating_power_percent}"
-        );
+        let status = self
+            .status_supporting(stove_id, Capability::AutoMode)
+            .await?;
+        self.limits_of(&status).heating_power.ensure(
+            "Heating power",
+            "power",
+            "",
+            heating_power_percent.into(),
+        )?;
         let params = StoveControlsParams {
             operating_mode: Some(OperatingMode::Auto.into()),
-            heating_power: Some(heating_power_percent.into()),
-            ..self.status(stove_id).await?.into_stove_controls()
+            heating_power: Some(heating_power_percent),
+            ..status.into_stove_controls()
         };
         Ok(self.stoves_api.stove_controls(params).await?)
     }
@@ -200,14 +319,22 @@ impl RikaFirenet for RikaFirenetClient {
         idle_temperature: u8,
         target_temperature: u8,
     ) -> Result<()> {
-        ensure!(
-            (12..21).contains(&idle_temperature),
-            "Idle temperature must be 12 <= temp <= 20°C but it was {idle_temperature}"
-        );
-        ensure!(
-            (14..29).contains(&target_temperature),
-            "Target temperature must be 14 <= temp <= 28°C but it was {target_temperature}"
-        );
+        let status = self
+            .status_supporting(stove_id, Capability::ComfortMode)
+            .await?;
+        let limits = self.limits_of(&status);
+        limits.idle_temperature.ensure(
+            "Idle temperature",
+            "temp",
+            "°C",
+            idle_temperature.into(),
+        )?;
+        limits.target_temperature.ensure(
+            "Target temperature",
+            "temp",
+            "°C",
+            target_temperature.into(),
+        )?;
         ensure!(
             idle_temperature < target_temperature,
             "Target temperature must be greater than idle temperature"
@@ -216,7 +343,7 @@ impl RikaFirenet for RikaFirenetClient {
             operating_mode: Some(OperatingMode::Comfort.into()),
             target_temperature: Some(target_temperature.to_string()),
             set_back_temperature: Some(idle_temperature.to_string()),
-            ..self.status(stove_id).await?.into_stove_controls()
+            ..status.into_stove_controls()
         };
         Ok(self.stoves_api.stove_controls(params).await?)
     }
@@ -226,14 +353,21 @@ impl RikaFirenet for RikaFirenetClient {
         stove_id: String,
         frost_protection_temperature: u8,
     ) -> Result<()> {
-        ensure!(
-            (4..10).contains(&frost_protection_temperature),
-            "Frost protection temperature must be 4 <= temp <= 10°C but it was {frost_protection_temperature}"
-        );
+        let status = self
+            .status_supporting(stove_id, Capability::FrostProtection)
+            .await?;
+        self.limits_of(&status)
+            .frost_protection_temperature
+            .ensure(
+                "Frost protection temperature",
+                "temp",
+                "°C",
+                frost_protection_temperature.into(),
+            )?;
         let params = StoveControlsParams {
             frost_protection_active: Some(true),
             frost_protection_temperature: Some(frost_protection_temperature.to_string()),
-            ..self.status(stove_id).await?.into_stove_controls()
+            ..status.into_stove_controls()
         };
         Ok(self.stoves_api.stove_controls(params).await?)
     }
@@ -241,34 +375,177 @@ impl RikaFirenet for RikaFirenetClient {
     async fn disable_frost_protection(&self, stove_id: String) -> Result<()> {
         let params = StoveControlsParams {
             frost_protection_active: Some(false),
-            ..self.status(stove_id).await?.into_stove_controls()
+            ..self
+                .status_supporting(stove_id, Capability::FrostProtection)
+                .await?
+                .into_stove_controls()
+        };
+        Ok(self.stoves_api.stove_controls(params).await?)
+    }
+
+    async fn set_bake_mode(&self, stove_id: String, bake_temperature: u16) -> Result<()> {
+        let status = self
+            .status_supporting(stove_id, Capability::BakeMode)
+            .await?;
+        self.limits_of(&status).bake_temperature.ensure(
+            "Bake temperature",
+            "temp",
+            "°C",
+            bake_temperature.into(),
+        )?;
+        let params = StoveControlsParams {
+            operating_mode: Some(OperatingMode::Bake.into()),
+            bake_temperature: Some(bake_temperature.to_string()),
+            ..status.into_stove_controls()
+        };
+        Ok(self.stoves_api.stove_controls(params).await?)
+    }
+
+    async fn set_temperature_offset(&self, stove_id: String, offset: f32) -> Result<()> {
+        let status = self
+            .status_supporting(stove_id, Capability::TemperatureOffset)
+            .await?;
+        self.limits_of(&status).temperature_offset.ensure(
+            "Temperature offset",
+            "offset",
+            "°C",
+            offset,
+        )?;
+        let params = StoveControlsParams {
+            temperature_offset: Some(Temperature::from_celsius(offset).to_string()),
+            ..status.into_stove_controls()
+        };
+        Ok(self.stoves_api.stove_controls(params).await?)
+    }
+
+    async fn set_room_power_request(
+        &self,
+        stove_id: String,
+        request: RoomPowerRequest,
+    ) -> Result<()> {
+        let params = StoveControlsParams {
+            room_power_request: Some(request.into()),
+            ..self
+                .status_supporting(stove_id, Capability::RoomPowerRequest)
+                .await?
+                .into_stove_controls()
+        };
+        Ok(self.stoves_api.stove_controls(params).await?)
+    }
+
+    async fn set_eco_mode(&self, stove_id: String, enabled: bool) -> Result<()> {
+        // turning eco mode off must remain possible once the stove stops supporting it
+        let status = if enabled {
+            self.status_supporting(stove_id, Capability::EcoMode)
+                .await?
+        } else {
+            self.status(stove_id).await?
+        };
+        let params = StoveControlsParams {
+            eco_mode: Some(enabled),
+            ..status.into_stove_controls()
+        };
+        Ok(self.stoves_api.stove_controls(params).await?)
+    }
+
+    async fn set_convection_fan(
+        &self,
+        stove_id: String,
+        fan: ConvectionFan,
+        settings: ConvectionFanSettings,
+    ) -> Result<()> {
+        let status = self
+            .status_supporting(stove_id, Capability::ConvectionFan(fan))
+            .await?;
+        let limits = self.limits_of(&status);
+        limits.convection_fan_level.ensure(
+            "Convection fan level",
+            "level",
+            "",
+            settings.level.into(),
+        )?;
+        limits.convection_fan_area.ensure(
+            "Convection fan area",
+            "area",
+            "",
+            settings.area.into(),
+        )?;
+        let active = Some(settings.active);
+        let level = Some(settings.level.into());
+        let area = Some(settings.area.into());
+        let params = match fan {
+            ConvectionFan::First => StoveControlsParams {
+                convection_fan1_active: active,
+                convection_fan1_level: level,
+                convection_fan1_area: area,
+                ..status.into_stove_controls()
+            },
+            ConvectionFan::Second => StoveControlsParams {
+                convection_fan2_active: active,
+                convection_fan2_level: level,
+                convection_fan2_area: area,
+                ..status.into_stove_controls()
+            },
         };
         Ok(self.stoves_api.stove_controls(params).await?)
     }
 
     async fn enable_schedule(&self, stove_id: String, schedule: HeatingSchedule) -> Result<()> {
+        schedule.ensure_valid()?;
         let params = StoveControlsParams {
             heating_times_active_for_comfort: Some(true),
-            heating_time_mon1: Some(schedule.monday.first.into()),
-            heating_time_mon2: Some(schedule.monday.second.into()),
-            heating_time_tue1: Some(schedule.tuesday.first.into()),
-            heating_time_tue2: Some(schedule.tuesday.second.into()),
-            heating_time_wed1: Some(schedule.wednesday.first.into()),
-            heating_time_wed2: Some(schedule.wednesday.second.into()),
-            heating_time_thu1: Some(schedule.thursday.first.into()),
-            heating_time_thu2: Some(schedule.thursday.second.into()),
-            heating_time_fri1: Some(schedule.friday.first.into()),
-            heating_time_fri2: Some(schedule.friday.second.into()),
-            heating_time_sat1: Some(schedule.saturday.first.into()),
-            heating_time_sat2: Some(schedule.saturday.second.into()),
-            heating_time_sun1: Some(schedule.sunday.first.into()),
-            heating_time_sun2: Some(schedule.sunday.second.into()),
-            ..self.status(stove_id).await?.into_stove_controls()
+            ..self
+                .status_supporting(stove_id, Capability::HeatingSchedule)
+                .await?
+                .into_stove_controls()
+                .with_schedule(schedule)
+        };
+        Ok(self.stoves_api.stove_controls(params).await?)
+    }
+
+    async fn disable_schedule(&self, stove_id: String) -> Result<()> {
+        let params = StoveControlsParams {
+            heating_times_active_for_comfort: Some(false),
+            ..self
+                .status_supporting(stove_id, Capability::HeatingSchedule)
+                .await?
+                .into_stove_controls()
         };
-        println!("{params:?}");
         Ok(self.stoves_api.stove_controls(params).await?)
     }
 
+    async fn update_day(
+        &self,
+        stove_id: String,
+        weekday: Weekday,
+        day_schedule: DailySchedule,
+    ) -> Result<()> {
+        let status = self
+            .status_supporting(stove_id, Capability::HeatingSchedule)
+            .await?;
+        let mut schedule = status.get_heating_schedule();
+        *schedule.day_mut(weekday) = day_schedule;
+        schedule.ensure_valid_day(weekday)?;
+        let params = status.into_stove_controls().with_schedule(schedule);
+        Ok(self.stoves_api.stove_controls(params).await?)
+    }
+
+    async fn copy_day(&self, stove_id: String, from: Weekday, to: Weekday) -> Result<()> {
+        let status = self
+            .status_supporting(stove_id, Capability::HeatingSchedule)
+            .await?;
+        let mut schedule = status.get_heating_schedule();
+        *schedule.day_mut(to) = schedule.day(from).clone();
+        let params = status.into_stove_controls().with_schedule(schedule);
+        Ok(self.stoves_api.stove_controls(params).await?)
+    }
+
+    async fn capabilities(&self, stove_id: String) -> Result<StoveCapabilities> {
+        Ok(self
+            .capabilities
+            .capabilities_of(&self.status(stove_id).await?))
+    }
+
     async fn logout(&self) -> Result<()> {
         Ok(self.auth_api.logout().await?)
     }
@@ -292,6 +569,32 @@ trait IntoStoveControlsParams {
     fn into_stove_controls(self) -> StoveControlsParams;
 }
 
+trait WithHeatingSchedule {
+    fn with_schedule(self, schedule: HeatingSchedule) -> Self;
+}
+
+impl WithHeatingSchedule for StoveControlsParams {
+    fn with_schedule(self, schedule: HeatingSchedule) -> Self {
+        StoveControlsParams {
+            heating_time_mon1: Some(schedule.monday.first.into()),
+            heating_time_mon2: Some(schedule.monday.second.into()),
+            heating_time_tue1: Some(schedule.tuesday.first.into()),
+            heating_time_tue2: Some(schedule.tuesday.second.into()),
+            heating_time_wed1: Some(schedule.wednesday.first.into()),
+            heating_time_wed2: Some(schedule.wednesday.second.into()),
+            heating_time_thu1: Some(schedule.thursday.first.into()),
+            heating_time_thu2: Some(schedule.thursday.second.into()),
+            heating_time_fri1: Some(schedule.friday.first.into()),
+            heating_time_fri2: Some(schedule.friday.second.into()),
+            heating_time_sat1: Some(schedule.saturday.first.into()),
+            heating_time_sat2: Some(schedule.saturday.second.into()),
+            heating_time_sun1: Some(schedule.sunday.first.into()),
+            heating_time_sun2: Some(schedule.sunday.second.into()),
+            ..self
+        }
+    }
+}
+
 impl IntoStoveControlsParams for StoveStatus {
     fn into_stove_controls(self) -> StoveControlsParams {
         StoveControlsParams {
@@ -343,27 +646,63 @@ pub trait HasDetailledStatus {
     fn get_heating_schedule(&self) -> HeatingSchedule;
 }
 
+/// Status readings beyond [`HasDetailledStatus`], kept in their own trait so that growing them
+/// doesn't break existing [`HasDetailledStatus`] implementations.
+pub trait HasStatusReadings {
+    /// Interprets the stove state, keeping the inputs used and the reason of an
+    /// [`StatusDetail::Unknown`] fallback.
+    fn interpret_status(&self) -> StatusInterpretation;
+    /// The status detail, failing with the reason instead of falling back to
+    /// [`StatusDetail::Unknown`], see [`HasStatusReadings::interpret_status`] for the inputs.
+    fn try_status_details(&self) -> Result<StatusDetail> {
+        self.interpret_status().into_result()
+    }
+    /// Current fan settings as sent by Firenet, `None` when the stove doesn't have this fan or
+    /// when Firenet sent a level or area which can't be represented.
+    fn get_convection_fan(&self, fan: ConvectionFan) -> Option<ConvectionFanSettings>;
+    /// Current oven temperature, `None` when the stove doesn't have an oven.
+    fn get_oven_temperature(&self) -> Option<Temperature>;
+    /// Oven target temperature, `None` when the stove doesn't have an oven or it isn't set.
+    fn get_bake_temperature(&self) -> Option<Temperature>;
+    fn get_room_power_request(&self) -> Option<RoomPowerRequest>;
+    /// Whether the stove currently runs according to an external thermostat request.
+    fn is_obeying_external_request(&self) -> bool;
+    /// Error, warning and service codes decoded with `faults`, empty when the stove runs fine.
+    fn active_faults(&self, faults: &FaultTable) -> Vec<StoveFault>;
+    /// Error counters, compare snapshots with [`faults::increased_error_counts`].
+    fn error_counts(&self) -> [ErrorCount; 20];
+}
+
 impl HasDetailledStatus for StoveStatus {
     fn get_status_details(&self) -> StatusDetail {
+        self.interpret_status().detail
+    }
+
+    fn get_heating_schedule(&self) -> HeatingSchedule {
+        HeatingSchedule::from(self.controls.clone())
+    }
+}
+
+impl HasStatusReadings for StoveStatus {
+    fn interpret_status(&self) -> StatusInterpretation {
         let frost_started = self.sensors.status_frost_started;
         let main_state = self.sensors.status_main_state;
         let sub_state = self.sensors.status_sub_state;
-        let bake_mode = self.controls.operating_mode.unwrap_or_default() == 3
-            && "1024" != self.controls.bake_temperature.clone().unwrap_or_default()
-            && "1024" != self.sensors.input_bake_temperature;
-        let temp_diff = self
-            .sensors
-            .input_bake_temperature
-            .parse::<i32>()
-            .unwrap()
-            .abs_diff(
-                self.controls
-                    .bake_temperature
-                    .clone()
-                    .unwrap_or_default()
-                    .parse::<i32>()
-                    .unwrap(),
-            );
+        let bake_inputs = BakeModeInputs {
+            operating_mode: self.controls.operating_mode,
+            bake_temperature: self.controls.bake_temperature(),
+            input_bake_temperature: self.sensors.input_bake_temperature(),
+        };
+        let interpretation =
+            |detail: StatusDetail, unknown_reason: Option<String>| StatusInterpretation {
+                detail,
+                main_state,
+                sub_state,
+                frost_started,
+                bake_inputs: bake_inputs.clone(),
+                unknown_reason,
+            };
+        let detail = |detail: StatusDetail| interpretation(detail, None);
 
         // based on rika-firenet.com algorithm:
         //     if (frostStarted) {
@@ -404,69 +743,169 @@ impl HasDetailledStatus for StoveStatus {
         //     return "Unknown";
         // }
         if frost_started {
-            return StatusDetail::FrostProtection;
+            return detail(StatusDetail::FrostProtection);
         }
-        if main_state == 1 {
-            if sub_state == 0 {
-                return StatusDetail::Off;
-            } else if sub_state == 1 {
-                return StatusDetail::Standby;
-            } else if sub_state == 2 {
-                return StatusDetail::ExternalRequest;
-            } else if sub_state == 3 {
-                return StatusDetail::Standby;
-            }
-            return StatusDetail::Unknown;
-        } else if main_state == 2 {
-            return StatusDetail::Ignition;
-        } else if main_state == 3 {
-            return StatusDetail::Startup;
-        } else if main_state == 4 {
-            if bake_mode && temp_diff < 10 {
-                return StatusDetail::Baking;
-            } else if bake_mode {
-                return StatusDetail::HeatingUp;
-            } else {
-                return StatusDetail::Running;
-            }
-        } else if main_state == 5 {
-            if sub_state == 3 || sub_state == 4 {
-                return StatusDetail::DeepCleaning;
-            } else {
-                return StatusDetail::Cleaning;
-            }
-        } else if main_state == 6 {
-            return StatusDetail::BurnOff;
-        } else if main_state == 11
-            || main_state == 13
-            || main_state == 14
-            || main_state == 16
-            || main_state == 17
-            || main_state == 50
-        {
-            return StatusDetail::SplitLogCheck;
-        } else if main_state == 20 || main_state == 21 {
-            return StatusDetail::SplitLogMode;
+        match main_state {
+            1 => match sub_state {
+                0 => detail(StatusDetail::Off),
+                1 | 3 => detail(StatusDetail::Standby),
+                2 => detail(StatusDetail::ExternalRequest),
+                _ => interpretation(
+                    StatusDetail::Unknown,
+                    Some(format!(
+                        "unknown substate {sub_state} for main state {main_state}"
+                    )),
+                ),
+            },
+            2 => detail(StatusDetail::Ignition),
+            3 => detail(StatusDetail::Startup),
+            4 => match bake_inputs.temperature_difference() {
+                Some(temp_diff) if temp_diff < 10.0 => detail(StatusDetail::Baking),
+                Some(_) => detail(StatusDetail::HeatingUp),
+                None => detail(StatusDetail::Running),
+            },
+            5 if sub_state == 3 || sub_state == 4 => detail(StatusDetail::DeepCleaning),
+            5 => detail(StatusDetail::Cleaning),
+            6 => detail(StatusDetail::BurnOff),
+            11 | 13 | 14 | 16 | 17 | 50 => detail(StatusDetail::SplitLogCheck),
+            20 | 21 => detail(StatusDetail::SplitLogMode),
+            _ => interpretation(
+                StatusDetail::Unknown,
+                Some(format!("unknown main state {main_state}")),
+            ),
         }
-        return StatusDetail::Unknown;
     }
 
-    fn get_heating_schedule(&self) -> HeatingSchedule {
-        HeatingSchedule::from(self.controls.clone())
+    fn get_convection_fan(&self, fan: ConvectionFan) -> Option<ConvectionFanSettings> {
+        let (supported, active, level, area) = match fan {
+            ConvectionFan::First => (
+                self.stove_features.multi_air1,
+                self.controls.convection_fan1_active,
+                self.controls.convection_fan1_level,
+                self.controls.convection_fan1_area,
+            ),
+            ConvectionFan::Second => (
+                self.stove_features.multi_air2,
+                self.controls.convection_fan2_active,
+                self.controls.convection_fan2_level,
+                self.controls.convection_fan2_area,
+            ),
+        };
+        if !supported {
+            return None;
+        }
+        let (level, area) = (level.unwrap_or_default(), area.unwrap_or_default());
+        let (Ok(level), Ok(area)) = (u8::try_from(level), i8::try_from(area)) else {
+            warn!(
+                "Ignoring {fan:?} convection fan of stove {}: level {level} or area {area} is out of range",
+                self.stove_id
+            );
+            return None;
+        };
+        Some(ConvectionFanSettings {
+            active: active.unwrap_or_default(),
+            level,
+            area,
+        })
+    }
+
+    fn get_oven_temperature(&self) -> Option<Temperature> {
+        self.sensors
+            .input_bake_temperature()
+            .filter(|_| self.stove_features.bake_mode)
+    }
+
+    fn get_bake_temperature(&self) -> Option<Temperature> {
+        self.controls
+            .bake_temperature()
+            .filter(|_| self.stove_features.bake_mode)
+    }
+
+    fn get_room_power_request(&self) -> Option<RoomPowerRequest> {
+        self.controls
+            .room_power_request
+            .and_then(|level| RoomPowerRequest::parse(level).ok())
+    }
+
+    fn is_obeying_external_request(&self) -> bool {
+        self.get_status_details() == StatusDetail::ExternalRequest
+    }
+
+    fn active_faults(&self, faults: &FaultTable) -> Vec<StoveFault> {
+        [
+            (
+                FaultSource::Error,
+                self.sensors.status_error,
+                self.sensors.status_sub_error,
+            ),
+            (FaultSource::Warning, self.sensors.status_warning, 0),
+            (FaultSource::Service, self.sensors.status_service, 0),
+        ]
+        .into_iter()
+        .filter(|(_, code, _)| *code != 0)
+        .map(|(source, code, sub_code)| StoveFault::decode(faults, source, code, sub_code))
+        .collect()
+    }
+
+    fn error_counts(&self) -> [ErrorCount; 20] {
+        let s = &self.sensors;
+        let counts = [
+            s.parameter_error_count0,
+            s.parameter_error_count1,
+            s.parameter_error_count2,
+            s.parameter_error_count3,
+            s.parameter_error_count4,
+            s.parameter_error_count5,
+            s.parameter_error_count6,
+            s.parameter_error_count7,
+            s.parameter_error_count8,
+            s.parameter_error_count9,
+            s.parameter_error_count10,
+            s.parameter_error_count11,
+            s.parameter_error_count12,
+            s.parameter_error_count13,
+            s.parameter_error_count14,
+            s.parameter_error_count15,
+            s.parameter_error_count16,
+            s.parameter_error_count17,
+            s.parameter_error_count18,
+            s.parameter_error_count19,
+        ];
+        std::array::from_fn(|index| ErrorCount::new(index, counts[index]))
     }
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use crate::{
-        HasDetailledStatus, RikaFirenet, RikaFirenetClient, extract_stove_ids,
-        model::{DailySchedule, HeatingSchedule, StatusDetail},
+        HasDetailledStatus, HasStatusReadings, RikaFirenet, RikaFirenetClient, Temperature,
+        capabilities::Capability,
+        error::StoveControlError,
+        extract_stove_ids,
+        faults::{FaultDefinition, FaultSeverity, FaultSource, FaultTable},
+        limits::{Limit, LimitsTable, StoveLimits},
+        model::{
+            ConvectionFan, ConvectionFanSettings, DailySchedule, HeatPeriod, HeatingSchedule,
+            RoomPowerRequest, StatusDetail, Weekday,
+        },
     };
     use httpmock::{
         Method::{GET, POST},
         MockServer,
     };
+    use proptest::prelude::*;
     use regex::Regex;
+    use rika_firenet_openapi::models::{StoveControls, StoveSensors, StoveStatus};
+
+    /// The status of `../mock/src/stove-status.json`, for stove `stove_id`.
+    pub(crate) fn mock_status(stove_id: &str) -> StoveStatus {
+        serde_json::from_str(
+            &std::fs::read_to_string("../mock/src/stove-status.json")
+                .unwrap()
+                .replace("__stove_id__", stove_id),
+        )
+        .unwrap()
+    }
 
     const PARTIAL_SUMMARY_EXAMPLE: &str = r#"
     <div role="main" class="ui-content">
@@ -489,7 +928,7 @@ mod tests {
 
     #[test]
     fn can_extract_stove_ids() {
-        let actual = extract_stove_ids(&PARTIAL_SUMMARY_EXAMPLE.to_string());
+        let actual = extract_stove_ids(PARTIAL_SUMMARY_EXAMPLE);
         let expected = vec!["12345".to_string(), "333444".to_string()];
         assert_eq!(actual, expected)
     }
@@ -537,6 +976,16 @@ mod tests {
             status.sensors.input_room_temperature, "19.6",
             "sensor value"
         );
+        assert_eq!(
+            status.sensors.input_room_temperature(),
+            Some(Temperature::from_celsius(19.6)),
+            "typed sensor value"
+        );
+        assert_eq!(
+            status.sensors.input_bake_temperature(),
+            None,
+            "unavailable sensor value"
+        );
         status_mock.assert();
     }
 
@@ -567,6 +1016,169 @@ mod tests {
         status_mock.assert();
     }
 
+    fn stove_status(main_state: u8, sub_state: u8) -> StoveStatus {
+        StoveStatus {
+            sensors: StoveSensors {
+                status_main_state: main_state,
+                status_sub_state: sub_state,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn can_explain_unknown_status() {
+        let interpretation = stove_status(1, 7).interpret_status();
+        assert_eq!(interpretation.detail, StatusDetail::Unknown);
+        assert_eq!(
+            interpretation.unknown_reason.as_deref(),
+            Some("unknown substate 7 for main state 1")
+        );
+        assert_eq!(
+            stove_status(1, 7)
+                .try_status_details()
+                .unwrap_err()
+                .to_string(),
+            "Can't interpret stove state (main state 1, substate 7): unknown substate 7 for main state 1"
+        );
+
+        let interpretation = stove_status(42, 0).interpret_status();
+        assert_eq!(
+            interpretation.unknown_reason.as_deref(),
+            Some("unknown main state 42")
+        );
+
+        let interpretation = stove_status(1, 3).interpret_status();
+        assert_eq!(interpretation.unknown_reason, None);
+        assert_eq!(
+            stove_status(1, 3).try_status_details().unwrap(),
+            StatusDetail::Standby
+        );
+    }
+
+    #[test]
+    fn can_interpret_status_with_missing_bake_temperature() {
+        let mut status = stove_status(4, 0);
+        status.controls.operating_mode = Some(3);
+        status.controls.bake_temperature = None;
+        status.sensors.input_bake_temperature = "".to_string();
+        assert_eq!(status.get_status_details(), StatusDetail::Running);
+
+        status.controls.bake_temperature = Some("230".to_string());
+        status.sensors.input_bake_temperature = "180".to_string();
+        let interpretation = status.interpret_status();
+        assert_eq!(interpretation.detail, StatusDetail::HeatingUp);
+        assert_eq!(
+            interpretation.bake_inputs.temperature_difference(),
+            Some(50.0)
+        );
+
+        status.sensors.input_bake_temperature = "225".to_string();
+        assert_eq!(status.get_status_details(), StatusDetail::Baking);
+    }
+
+    proptest! {
+        #[test]
+        fn never_panics_interpreting_status(
+            main_state in any::<u8>(),
+            sub_state in any::<u8>(),
+            frost_started in any::<bool>(),
+            operating_mode in proptest::option::of(any::<u8>()),
+            bake_temperature in proptest::option::of(".*"),
+            input_bake_temperature in ".*",
+        ) {
+            let status = StoveStatus {
+                controls: StoveControls {
+                    operating_mode,
+                    bake_temperature,
+                    ..Default::default()
+                },
+                sensors: StoveSensors {
+                    status_main_state: main_state,
+                    status_sub_state: sub_state,
+                    status_frost_started: frost_started,
+                    input_bake_temperature,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let interpretation = status.interpret_status();
+            prop_assert_eq!(
+                interpretation.unknown_reason.is_some(),
+                interpretation.detail == StatusDetail::Unknown
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn can_get_stove_capabilities() {
+        let server = MockServer::start();
+        let status_mock = server.mock(|when, then| {
+            when.method(GET).path("/api/client/__stove_id__/status");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("../mock/src/stove-status.json");
+        });
+
+        let client = RikaFirenetClient::builder()
+            .base_url(server.base_url())
+            .build("someone@rika.com", "Secret!");
+
+        let capabilities = client
+            .capabilities("__stove_id__".to_owned())
+            .await
+            .expect("a successful operation");
+
+        assert_eq!(capabilities.stove_type, "DOMO");
+        assert_eq!(capabilities.stove_type_number, 22);
+        assert!(capabilities.supports(Capability::ConvectionFan(ConvectionFan::First)));
+        assert!(!capabilities.supports(Capability::ConvectionFan(ConvectionFan::Second)));
+        assert!(!capabilities.supports(Capability::BakeMode));
+        assert!(!capabilities.supports(Capability::EcoMode));
+        status_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn cant_restore_controls_the_stove_lacks() {
+        let server = MockServer::start();
+        let status_mock = server.mock(|when, then| {
+            when.method(GET).path("/api/client/__stove_id__/status");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("../mock/src/stove-status.json");
+        });
+        let control_mock = server.mock(|when, then| {
+            when.method(POST).path("/api/client/__stove_id__/controls");
+            then.status(200).body("OK");
+        });
+
+        let client = RikaFirenetClient::builder()
+            .base_url(server.base_url())
+            .build("someone@rika.com", "Secret!");
+        let mut controls = client
+            .status("__stove_id__".to_owned())
+            .await
+            .unwrap()
+            .controls;
+        controls.convection_fan2_active = Some(true);
+
+        let error = client
+            .restore_controls("__stove_id__".to_owned(), controls)
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            error.downcast_ref::<StoveControlError>(),
+            Some(&StoveControlError::unsupported(
+                "__stove_id__",
+                ConvectionFan::Second.to_string()
+            ))
+        );
+        status_mock.assert_calls(2);
+        control_mock.assert_calls(0);
+    }
+
     #[tokio::test]
     async fn can_turn_on_stove() {
         let server = MockServer::start();
@@ -660,8 +1272,19 @@ mod tests {
 
     #[tokio::test]
     async fn cant_set_stove_mode_to_manual_with_an_invalid_power_heating_value() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/api/client/__stove_id__/status");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("../mock/src/stove-status.json");
+        });
+        let control_mock = server.mock(|when, then| {
+            when.method(POST).path("/api/client/__stove_id__/controls");
+            then.status(200).body("OK");
+        });
         let client = RikaFirenetClient::builder()
-            .base_url("http://localhost")
+            .base_url(server.base_url())
             .build("someone@rika.com", "Secret!");
 
         let error = client
@@ -673,6 +1296,7 @@ mod tests {
             format!("{root_cause}"),
             "Heating power must be 0 <= power <= 100 but it was 101"
         );
+        control_mock.assert_calls(0);
     }
 
     #[tokio::test]
@@ -708,12 +1332,23 @@ mod tests {
 
     #[tokio::test]
     async fn cant_set_stove_mode_to_auto_with_an_invalid_power_heating_value() {
-        let client = RikaFirenetClient::builder()
-            .base_url("http://localhost")
-            .build("someone@rika.com", "Secret!");
-
-        let error = client
-            .set_auto_mode("__stove_id__".to_owned(), 101)
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/api/client/__stove_id__/status");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("../mock/src/stove-status.json");
+        });
+        let control_mock = server.mock(|when, then| {
+            when.method(POST).path("/api/client/__stove_id__/controls");
+            then.status(200).body("OK");
+        });
+        let client = RikaFirenetClient::builder()
+            .base_url(server.base_url())
+            .build("someone@rika.com", "Secret!");
+
+        let error = client
+            .set_auto_mode("__stove_id__".to_owned(), 101)
             .await
             .unwrap_err();
         let root_cause = error.root_cause();
@@ -721,6 +1356,7 @@ mod tests {
             format!("{root_cause}"),
             "Heating power must be 0 <= power <= 100 but it was 101"
         );
+        control_mock.assert_calls(0);
     }
 
     #[tokio::test]
@@ -757,8 +1393,19 @@ mod tests {
 
     #[tokio::test]
     async fn cant_set_stove_mode_to_comfort_with_an_invalid_target_or_idle_temperature_value() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/api/client/__stove_id__/status");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("../mock/src/stove-status.json");
+        });
+        let control_mock = server.mock(|when, then| {
+            when.method(POST).path("/api/client/__stove_id__/controls");
+            then.status(200).body("OK");
+        });
         let client = RikaFirenetClient::builder()
-            .base_url("http://localhost")
+            .base_url(server.base_url())
             .build("someone@rika.com", "Secret!");
 
         let error = client
@@ -810,6 +1457,88 @@ mod tests {
             format!("{root_cause}"),
             "Target temperature must be greater than idle temperature"
         );
+        control_mock.assert_calls(0);
+    }
+
+    #[tokio::test]
+    async fn can_use_the_upper_bounds_of_the_limits() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/api/client/__stove_id__/status");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("../mock/src/stove-status.json");
+        });
+        let control_mock = server.mock(|when, then| {
+            when.method(POST).path("/api/client/__stove_id__/controls");
+            then.status(200).body("OK");
+        });
+        let client = RikaFirenetClient::builder()
+            .base_url(server.base_url())
+            .build("someone@rika.com", "Secret!");
+
+        client
+            .set_manual_mode("__stove_id__".to_owned(), 100)
+            .await
+            .expect("a successful operation");
+        client
+            .set_auto_mode("__stove_id__".to_owned(), 100)
+            .await
+            .expect("a successful operation");
+        client
+            .set_comfort_mode("__stove_id__".to_owned(), 20, 28)
+            .await
+            .expect("a successful operation");
+        client
+            .enable_frost_protection("__stove_id__".to_owned(), 10)
+            .await
+            .expect("a successful operation");
+
+        control_mock.assert_calls(4);
+    }
+
+    #[tokio::test]
+    async fn can_use_custom_limits_for_a_model() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/api/client/__stove_id__/status");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("../mock/src/stove-status.json");
+        });
+        let control_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/api/client/__stove_id__/controls")
+                .body_matches(Regex::new("(^|&)targetTemperature=30(&|$)").unwrap());
+            then.status(200).body("OK");
+        });
+        let client = RikaFirenetClient::builder()
+            .base_url(server.base_url())
+            .limits(LimitsTable::default().with_model(
+                "RIKA",
+                "DOMO",
+                StoveLimits {
+                    target_temperature: Limit::new(14.0, 30.0, 1.0),
+                    ..Default::default()
+                },
+            ))
+            .build("someone@rika.com", "Secret!");
+
+        client
+            .set_comfort_mode("__stove_id__".to_owned(), 18, 30)
+            .await
+            .expect("a successful operation");
+        let error = client
+            .set_comfort_mode("__stove_id__".to_owned(), 18, 31)
+            .await
+            .unwrap_err();
+        let root_cause = error.root_cause();
+        assert_eq!(
+            format!("{root_cause}"),
+            "Target temperature must be 14 <= temp <= 30°C but it was 31"
+        );
+
+        control_mock.assert();
     }
 
     #[tokio::test]
@@ -858,6 +1587,255 @@ mod tests {
         control_mock.assert();
     }
 
+    #[tokio::test]
+    async fn cant_configure_an_invalid_schedule() {
+        let client = RikaFirenetClient::builder()
+            .base_url("http://localhost")
+            .build("someone@rika.com", "Secret!");
+
+        let schedule = HeatingSchedule::all_same(DailySchedule::dual(
+            "18002200".parse().unwrap(),
+            "07001000".parse().unwrap(),
+        ));
+        let error = client
+            .enable_schedule("__stove_id__".to_owned(), schedule)
+            .await
+            .unwrap_err();
+        let root_cause = error.root_cause();
+        assert!(
+            format!("{root_cause}").starts_with(
+                "Invalid heating schedule: monday: second period begins before the first one"
+            ),
+            "unexpected error: {root_cause}"
+        );
+    }
+
+    #[tokio::test]
+    async fn can_disable_schedule() {
+        let server = MockServer::start();
+        let status_mock = server.mock(|when, then| {
+            when.method(GET).path("/api/client/__stove_id__/status");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("../mock/src/stove-status.json");
+        });
+        let control_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/api/client/__stove_id__/controls")
+                .body_matches(Regex::new("(^|&)revision=1572181181(&|$)").unwrap())
+                .body_matches(Regex::new("(^|&)heatingTimesActiveForComfort=false(&|$)").unwrap())
+                .body_matches(Regex::new("(^|&)heatingTimeMon1=07001000(&|$)").unwrap());
+            then.status(200).body("OK");
+        });
+
+        let client = RikaFirenetClient::builder()
+            .base_url(server.base_url())
+            .build("someone@rika.com", "Secret!");
+
+        client
+            .disable_schedule("__stove_id__".to_owned())
+            .await
+            .expect("a successful operation");
+
+        status_mock.assert();
+        control_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn can_get_schedule() {
+        let server = MockServer::start();
+        let status_mock = server.mock(|when, then| {
+            when.method(GET).path("/api/client/__stove_id__/status");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("../mock/src/stove-status.json");
+        });
+
+        let client = RikaFirenetClient::builder()
+            .base_url(server.base_url())
+            .build("someone@rika.com", "Secret!");
+
+        let schedule = client
+            .get_schedule("__stove_id__".to_owned())
+            .await
+            .expect("a successful operation");
+
+        assert_eq!(
+            schedule,
+            HeatingSchedule::week_vs_end_days(
+                DailySchedule::dual(
+                    HeatPeriod::new(7, 0, 10, 0).unwrap(),
+                    HeatPeriod::new(17, 0, 22, 0).unwrap(),
+                ),
+                DailySchedule::single(HeatPeriod::new(7, 0, 23, 0).unwrap()),
+            )
+        );
+        status_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn can_update_one_day_of_the_schedule() {
+        let server = MockServer::start();
+        let status_mock = server.mock(|when, then| {
+            when.method(GET).path("/api/client/__stove_id__/status");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("../mock/src/stove-status.json");
+        });
+        let control_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/api/client/__stove_id__/controls")
+                .body_matches(Regex::new("(^|&)revision=1572181181(&|$)").unwrap())
+                .body_matches(Regex::new("(^|&)heatingTimesActiveForComfort=true(&|$)").unwrap())
+                .body_matches(Regex::new("(^|&)heatingTimeMon1=07001000(&|$)").unwrap())
+                .body_matches(Regex::new("(^|&)heatingTimeWed1=06300800(&|$)").unwrap())
+                .body_matches(Regex::new("(^|&)heatingTimeWed2=12002300(&|$)").unwrap())
+                .body_matches(Regex::new("(^|&)heatingTimeSun1=07002300(&|$)").unwrap());
+            then.status(200).body("OK");
+        });
+
+        let client = RikaFirenetClient::builder()
+            .base_url(server.base_url())
+            .build("someone@rika.com", "Secret!");
+
+        client
+            .update_day(
+                "__stove_id__".to_owned(),
+                Weekday::Wednesday,
+                DailySchedule::dual(
+                    HeatPeriod::new(6, 30, 8, 0).unwrap(),
+                    HeatPeriod::new(12, 0, 23, 0).unwrap(),
+                ),
+            )
+            .await
+            .expect("a successful operation");
+
+        status_mock.assert();
+        control_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn can_copy_one_day_of_the_schedule_to_another() {
+        let server = MockServer::start();
+        let status_mock = server.mock(|when, then| {
+            when.method(GET).path("/api/client/__stove_id__/status");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("../mock/src/stove-status.json");
+        });
+        let control_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/api/client/__stove_id__/controls")
+                .body_matches(Regex::new("(^|&)revision=1572181181(&|$)").unwrap())
+                .body_matches(Regex::new("(^|&)heatingTimeFri1=07002300(&|$)").unwrap())
+                .body_matches(Regex::new("(^|&)heatingTimeFri2=00000000(&|$)").unwrap())
+                .body_matches(Regex::new("(^|&)heatingTimeThu1=07001000(&|$)").unwrap())
+                .body_matches(Regex::new("(^|&)heatingTimeSat1=07002300(&|$)").unwrap());
+            then.status(200).body("OK");
+        });
+
+        let client = RikaFirenetClient::builder()
+            .base_url(server.base_url())
+            .build("someone@rika.com", "Secret!");
+
+        client
+            .copy_day(
+                "__stove_id__".to_owned(),
+                Weekday::Saturday,
+                Weekday::Friday,
+            )
+            .await
+            .expect("a successful operation");
+
+        status_mock.assert();
+        control_mock.assert();
+    }
+
+    /// Implements the operations of the first release only.
+    #[derive(Default)]
+    struct FirstReleaseFirenet {
+        enabled_schedule: std::sync::Mutex<Option<HeatingSchedule>>,
+    }
+
+    #[async_trait::async_trait]
+    impl RikaFirenet for FirstReleaseFirenet {
+        async fn list_stoves(&self) -> anyhow::Result<Vec<String>> {
+            anyhow::bail!("not used by this test")
+        }
+        async fn status(&self, stove_id: String) -> anyhow::Result<StoveStatus> {
+            Ok(mock_status(&stove_id))
+        }
+        async fn restore_controls(&self, _: String, _: StoveControls) -> anyhow::Result<()> {
+            anyhow::bail!("not used by this test")
+        }
+        async fn turn_on(&self, _: String) -> anyhow::Result<()> {
+            anyhow::bail!("not used by this test")
+        }
+        async fn turn_off(&self, _: String) -> anyhow::Result<()> {
+            anyhow::bail!("not used by this test")
+        }
+        async fn set_manual_mode(&self, _: String, _: u8) -> anyhow::Result<()> {
+            anyhow::bail!("not used by this test")
+        }
+        async fn set_auto_mode(&self, _: String, _: u8) -> anyhow::Result<()> {
+            anyhow::bail!("not used by this test")
+        }
+        async fn set_comfort_mode(&self, _: String, _: u8, _: u8) -> anyhow::Result<()> {
+            anyhow::bail!("not used by this test")
+        }
+        async fn enable_frost_protection(&self, _: String, _: u8) -> anyhow::Result<()> {
+            anyhow::bail!("not used by this test")
+        }
+        async fn disable_frost_protection(&self, _: String) -> anyhow::Result<()> {
+            anyhow::bail!("not used by this test")
+        }
+        async fn enable_schedule(
+            &self,
+            _: String,
+            schedule: HeatingSchedule,
+        ) -> anyhow::Result<()> {
+            *self.enabled_schedule.lock().unwrap() = Some(schedule);
+            Ok(())
+        }
+        async fn logout(&self) -> anyhow::Result<()> {
+            anyhow::bail!("not used by this test")
+        }
+    }
+
+    #[tokio::test]
+    async fn operations_added_later_have_default_implementations() {
+        let firenet = FirstReleaseFirenet::default();
+
+        let error = firenet
+            .set_bake_mode("__stove_id__".to_owned(), 200)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<StoveControlError>(),
+            Some(&StoveControlError::unsupported("__stove_id__", "bake mode"))
+        );
+        let capabilities = firenet
+            .capabilities("__stove_id__".to_owned())
+            .await
+            .unwrap();
+        assert_eq!(capabilities.stove_id, "__stove_id__");
+
+        firenet
+            .copy_day(
+                "__stove_id__".to_owned(),
+                Weekday::Saturday,
+                Weekday::Friday,
+            )
+            .await
+            .expect("a successful operation");
+
+        let schedule = firenet.enabled_schedule.lock().unwrap().take().unwrap();
+        assert_eq!(
+            schedule.day(Weekday::Friday),
+            schedule.day(Weekday::Saturday)
+        );
+    }
+
     #[tokio::test]
     async fn can_enable_frost_mode() {
         let server = MockServer::start();
@@ -891,8 +1869,19 @@ mod tests {
 
     #[tokio::test]
     async fn cant_enable_frost_protection_with_an_invalid_temperature_value() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/api/client/__stove_id__/status");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("../mock/src/stove-status.json");
+        });
+        let control_mock = server.mock(|when, then| {
+            when.method(POST).path("/api/client/__stove_id__/controls");
+            then.status(200).body("OK");
+        });
         let client = RikaFirenetClient::builder()
-            .base_url("http://localhost")
+            .base_url(server.base_url())
             .build("someone@rika.com", "Secret!");
 
         let error = client
@@ -914,6 +1903,7 @@ mod tests {
             format!("{root_cause}"),
             "Frost protection temperature must be 4 <= temp <= 10°C but it was 11"
         );
+        control_mock.assert_calls(0);
     }
 
     #[tokio::test]
@@ -946,6 +1936,540 @@ mod tests {
         control_mock.assert();
     }
 
+    fn stove_status_with_oven() -> String {
+        let mut status = mock_status("__stove_id__");
+        status.stove_features.bake_mode = true;
+        status.sensors.input_bake_temperature = "185".to_string();
+        serde_json::to_string(&status).unwrap()
+    }
+
+    #[tokio::test]
+    async fn can_set_bake_mode() {
+        let server = MockServer::start();
+        let status_mock = server.mock(|when, then| {
+            when.method(GET).path("/api/client/__stove_id__/status");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(stove_status_with_oven());
+        });
+        let control_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/api/client/__stove_id__/controls")
+                .body_matches(Regex::new("(^|&)revision=1572181181(&|$)").unwrap())
+                .body_matches(Regex::new("(^|&)operatingMode=3(&|$)").unwrap())
+                .body_matches(Regex::new("(^|&)bakeTemperature=200(&|$)").unwrap());
+            then.status(200).body("OK");
+        });
+
+        let client = RikaFirenetClient::builder()
+            .base_url(server.base_url())
+            .build("someone@rika.com", "Secret!");
+
+        client
+            .set_bake_mode("__stove_id__".to_owned(), 200)
+            .await
+            .expect("a successful operation");
+
+        status_mock.assert();
+        control_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn cant_set_bake_mode_when_the_stove_has_no_oven() {
+        let server = MockServer::start();
+        let status_mock = server.mock(|when, then| {
+            when.method(GET).path("/api/client/__stove_id__/status");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("../mock/src/stove-status.json");
+        });
+        let control_mock = server.mock(|when, then| {
+            when.method(POST).path("/api/client/__stove_id__/controls");
+            then.status(200).body("OK");
+        });
+
+        let client = RikaFirenetClient::builder()
+            .base_url(server.base_url())
+            .build("someone@rika.com", "Secret!");
+
+        let error = client
+            .set_bake_mode("__stove_id__".to_owned(), 200)
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            error.downcast_ref::<StoveControlError>(),
+            Some(&StoveControlError::unsupported("__stove_id__", "bake mode"))
+        );
+        status_mock.assert();
+        control_mock.assert_calls(0);
+    }
+
+    #[tokio::test]
+    async fn cant_set_bake_mode_with_an_invalid_temperature_value() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/api/client/__stove_id__/status");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(stove_status_with_oven());
+        });
+        let control_mock = server.mock(|when, then| {
+            when.method(POST).path("/api/client/__stove_id__/controls");
+            then.status(200).body("OK");
+        });
+        let client = RikaFirenetClient::builder()
+            .base_url(server.base_url())
+            .build("someone@rika.com", "Secret!");
+
+        let error = client
+            .set_bake_mode("__stove_id__".to_owned(), 251)
+            .await
+            .unwrap_err();
+        let root_cause = error.root_cause();
+        assert_eq!(
+            format!("{root_cause}"),
+            "Bake temperature must be 100 <= temp <= 250°C but it was 251"
+        );
+        control_mock.assert_calls(0);
+    }
+
+    #[test]
+    fn can_read_oven_temperatures() {
+        let mut status: StoveStatus = serde_json::from_str(&stove_status_with_oven()).unwrap();
+        assert_eq!(
+            status.get_oven_temperature(),
+            Some(Temperature::from_celsius(185.0))
+        );
+        assert_eq!(status.get_bake_temperature(), None);
+
+        status.controls.bake_temperature = Some("200".to_string());
+        assert_eq!(
+            status.get_bake_temperature(),
+            Some(Temperature::from_celsius(200.0))
+        );
+
+        status.stove_features.bake_mode = false;
+        assert_eq!(status.get_oven_temperature(), None);
+        assert_eq!(status.get_bake_temperature(), None);
+    }
+
+    #[tokio::test]
+    async fn can_set_temperature_offset() {
+        let server = MockServer::start();
+        let status_mock = server.mock(|when, then| {
+            when.method(GET).path("/api/client/__stove_id__/status");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("../mock/src/stove-status.json");
+        });
+        let control_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/api/client/__stove_id__/controls")
+                .body_matches(Regex::new("(^|&)revision=1572181181(&|$)").unwrap())
+                .body_matches(Regex::new("(^|&)temperatureOffset=-1.5(&|$)").unwrap());
+            then.status(200).body("OK");
+        });
+
+        let client = RikaFirenetClient::builder()
+            .base_url(server.base_url())
+            .build("someone@rika.com", "Secret!");
+
+        client
+            .set_temperature_offset("__stove_id__".to_owned(), -1.5)
+            .await
+            .expect("a successful operation");
+
+        status_mock.assert();
+        control_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn cant_set_temperature_offset_with_an_invalid_value() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/api/client/__stove_id__/status");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("../mock/src/stove-status.json");
+        });
+        let control_mock = server.mock(|when, then| {
+            when.method(POST).path("/api/client/__stove_id__/controls");
+            then.status(200).body("OK");
+        });
+        let client = RikaFirenetClient::builder()
+            .base_url(server.base_url())
+            .build("someone@rika.com", "Secret!");
+
+        let error = client
+            .set_temperature_offset("__stove_id__".to_owned(), 4.5)
+            .await
+            .unwrap_err();
+        let root_cause = error.root_cause();
+        assert_eq!(
+            format!("{root_cause}"),
+            "Temperature offset must be -4 <= offset <= 4°C but it was 4.5"
+        );
+
+        let error = client
+            .set_temperature_offset("__stove_id__".to_owned(), 1.2)
+            .await
+            .unwrap_err();
+        let root_cause = error.root_cause();
+        assert_eq!(
+            format!("{root_cause}"),
+            "Temperature offset must be a multiple of 0.5°C but it was 1.2"
+        );
+        control_mock.assert_calls(0);
+    }
+
+    #[tokio::test]
+    async fn can_set_room_power_request() {
+        let server = MockServer::start();
+        let status_mock = server.mock(|when, then| {
+            when.method(GET).path("/api/client/__stove_id__/status");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("../mock/src/stove-status.json");
+        });
+        let control_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/api/client/__stove_id__/controls")
+                .body_matches(Regex::new("(^|&)revision=1572181181(&|$)").unwrap())
+                .body_matches(Regex::new("(^|&)RoomPowerRequest=4(&|$)").unwrap());
+            then.status(200).body("OK");
+        });
+
+        let client = RikaFirenetClient::builder()
+            .base_url(server.base_url())
+            .build("someone@rika.com", "Secret!");
+
+        client
+            .set_room_power_request("__stove_id__".to_owned(), RoomPowerRequest::Maximum)
+            .await
+            .expect("a successful operation");
+
+        status_mock.assert();
+        control_mock.assert();
+    }
+
+    #[test]
+    fn can_read_external_request() {
+        let mut status = stove_status(1, 2);
+        status.controls.room_power_request = Some(3);
+        assert_eq!(
+            status.get_room_power_request(),
+            Some(RoomPowerRequest::High)
+        );
+        assert!(status.is_obeying_external_request());
+
+        status.sensors.status_sub_state = 1;
+        status.controls.room_power_request = Some(0);
+        assert_eq!(status.get_room_power_request(), None);
+        assert!(!status.is_obeying_external_request());
+    }
+
+    #[tokio::test]
+    async fn can_enable_eco_mode() {
+        let server = MockServer::start();
+        let status_mock = server.mock(|when, then| {
+            when.method(GET).path("/api/client/__stove_id__/status");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body({
+                    let mut status = mock_status("__stove_id__");
+                    status.sensors.parameter_eco_mode_possible = true;
+                    serde_json::to_string(&status).unwrap()
+                });
+        });
+        let control_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/api/client/__stove_id__/controls")
+                .body_matches(Regex::new("(^|&)revision=1572181181(&|$)").unwrap())
+                .body_matches(Regex::new("(^|&)ecoMode=true(&|$)").unwrap());
+            then.status(200).body("OK");
+        });
+
+        let client = RikaFirenetClient::builder()
+            .base_url(server.base_url())
+            .build("someone@rika.com", "Secret!");
+
+        client
+            .set_eco_mode("__stove_id__".to_owned(), true)
+            .await
+            .expect("a successful operation");
+
+        status_mock.assert();
+        control_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn cant_enable_eco_mode_when_the_stove_does_not_support_it() {
+        let server = MockServer::start();
+        let status_mock = server.mock(|when, then| {
+            when.method(GET).path("/api/client/__stove_id__/status");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("../mock/src/stove-status.json");
+        });
+        let control_mock = server.mock(|when, then| {
+            when.method(POST).path("/api/client/__stove_id__/controls");
+            then.status(200).body("OK");
+        });
+
+        let client = RikaFirenetClient::builder()
+            .base_url(server.base_url())
+            .build("someone@rika.com", "Secret!");
+
+        let error = client
+            .set_eco_mode("__stove_id__".to_owned(), true)
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            error.downcast_ref::<StoveControlError>(),
+            Some(&StoveControlError::unsupported("__stove_id__", "eco mode"))
+        );
+        assert_eq!(
+            error.to_string(),
+            "eco mode is unsupported by stove __stove_id__"
+        );
+        status_mock.assert();
+        control_mock.assert_calls(0);
+    }
+
+    #[tokio::test]
+    async fn can_disable_eco_mode_when_the_stove_does_not_support_it() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/api/client/__stove_id__/status");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("../mock/src/stove-status.json");
+        });
+        let control_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/api/client/__stove_id__/controls")
+                .body_matches(Regex::new("(^|&)ecoMode=false(&|$)").unwrap());
+            then.status(200).body("OK");
+        });
+
+        let client = RikaFirenetClient::builder()
+            .base_url(server.base_url())
+            .build("someone@rika.com", "Secret!");
+
+        client
+            .set_eco_mode("__stove_id__".to_owned(), false)
+            .await
+            .unwrap();
+
+        control_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn can_set_convection_fan() {
+        let server = MockServer::start();
+        let status_mock = server.mock(|when, then| {
+            when.method(GET).path("/api/client/__stove_id__/status");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("../mock/src/stove-status.json");
+        });
+        let control_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/api/client/__stove_id__/controls")
+                .body_matches(Regex::new("(^|&)revision=1572181181(&|$)").unwrap())
+                .body_matches(Regex::new("(^|&)convectionFan1Active=true(&|$)").unwrap())
+                .body_matches(Regex::new("(^|&)convectionFan1Level=3(&|$)").unwrap())
+                .body_matches(Regex::new("(^|&)convectionFan1Area=-10(&|$)").unwrap())
+                .body_matches(Regex::new("(^|&)convectionFan2Active=false(&|$)").unwrap());
+            then.status(200).body("OK");
+        });
+
+        let client = RikaFirenetClient::builder()
+            .base_url(server.base_url())
+            .build("someone@rika.com", "Secret!");
+
+        client
+            .set_convection_fan(
+                "__stove_id__".to_owned(),
+                ConvectionFan::First,
+                ConvectionFanSettings {
+                    active: true,
+                    level: 3,
+                    area: -10,
+                },
+            )
+            .await
+            .expect("a successful operation");
+
+        status_mock.assert();
+        control_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn cant_set_a_convection_fan_the_stove_does_not_have() {
+        let server = MockServer::start();
+        let status_mock = server.mock(|when, then| {
+            when.method(GET).path("/api/client/__stove_id__/status");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("../mock/src/stove-status.json");
+        });
+        let control_mock = server.mock(|when, then| {
+            when.method(POST).path("/api/client/__stove_id__/controls");
+            then.status(200).body("OK");
+        });
+
+        let client = RikaFirenetClient::builder()
+            .base_url(server.base_url())
+            .build("someone@rika.com", "Secret!");
+
+        let error = client
+            .set_convection_fan(
+                "__stove_id__".to_owned(),
+                ConvectionFan::Second,
+                ConvectionFanSettings::default(),
+            )
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            error.downcast_ref::<StoveControlError>(),
+            Some(&StoveControlError::unsupported(
+                "__stove_id__",
+                "convection fan 2"
+            ))
+        );
+        status_mock.assert();
+        control_mock.assert_calls(0);
+    }
+
+    #[tokio::test]
+    async fn cant_set_convection_fan_with_invalid_settings() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/api/client/__stove_id__/status");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("../mock/src/stove-status.json");
+        });
+        let control_mock = server.mock(|when, then| {
+            when.method(POST).path("/api/client/__stove_id__/controls");
+            then.status(200).body("OK");
+        });
+        let client = RikaFirenetClient::builder()
+            .base_url(server.base_url())
+            .build("someone@rika.com", "Secret!");
+
+        let error = client
+            .set_convection_fan(
+                "__stove_id__".to_owned(),
+                ConvectionFan::First,
+                ConvectionFanSettings {
+                    active: true,
+                    level: 6,
+                    area: 0,
+                },
+            )
+            .await
+            .unwrap_err();
+        let root_cause = error.root_cause();
+        assert_eq!(
+            format!("{root_cause}"),
+            "Convection fan level must be 0 <= level <= 5 but it was 6"
+        );
+
+        let error = client
+            .set_convection_fan(
+                "__stove_id__".to_owned(),
+                ConvectionFan::First,
+                ConvectionFanSettings {
+                    active: true,
+                    level: 2,
+                    area: 31,
+                },
+            )
+            .await
+            .unwrap_err();
+        let root_cause = error.root_cause();
+        assert_eq!(
+            format!("{root_cause}"),
+            "Convection fan area must be -30 <= area <= 30 but it was 31"
+        );
+        control_mock.assert_calls(0);
+    }
+
+    #[test]
+    fn can_list_active_faults() {
+        let table = FaultTable::default().with_fault(
+            FaultSource::Error,
+            2,
+            FaultDefinition {
+                severity: FaultSeverity::Critical,
+                description: "Ignition failed".to_string(),
+                suggested_action: "Empty the burn pot".to_string(),
+                reference: "Stove manual, page 42".to_string(),
+            },
+        );
+        let mut status = stove_status(1, 3);
+        assert_eq!(status.active_faults(&table), vec![]);
+
+        status.sensors.status_error = 2;
+        status.sensors.status_sub_error = 1;
+        status.sensors.status_service = 77;
+        let faults = status.active_faults(&table);
+
+        assert_eq!(faults.len(), 2);
+        assert_eq!(faults[0].source, FaultSource::Error);
+        assert_eq!(faults[0].description.as_deref(), Some("Ignition failed"));
+        assert_eq!(faults[0].sub_code, 1);
+        assert_eq!(faults[1].source, FaultSource::Service);
+        assert_eq!(faults[1].code, 77);
+        assert!(!faults[1].is_known());
+        assert_eq!(
+            faults.iter().map(|fault| fault.severity).max(),
+            Some(FaultSeverity::Critical)
+        );
+    }
+
+    #[test]
+    fn can_read_error_counts() {
+        let status = mock_status("__stove_id__");
+
+        let counts = status.error_counts();
+
+        assert_eq!(counts[0].count, 1);
+        assert!(counts[1..].iter().all(|counter| counter.count == 0));
+        assert!(
+            counts
+                .iter()
+                .enumerate()
+                .all(|(i, counter)| counter.index == i)
+        );
+    }
+
+    #[test]
+    fn can_read_convection_fans() {
+        let mut status = mock_status("__stove_id__");
+        status.controls.convection_fan1_active = Some(true);
+        status.controls.convection_fan1_level = Some(4);
+        status.controls.convection_fan1_area = Some(-20);
+
+        assert_eq!(
+            status.get_convection_fan(ConvectionFan::First),
+            Some(ConvectionFanSettings {
+                active: true,
+                level: 4,
+                area: -20,
+            })
+        );
+        assert_eq!(status.get_convection_fan(ConvectionFan::Second), None);
+
+        status.controls.convection_fan1_level = Some(-5);
+        assert_eq!(status.get_convection_fan(ConvectionFan::First), None);
+    }
+
     #[tokio::test]
     async fn can_logout() {
         let server = MockServer::start();
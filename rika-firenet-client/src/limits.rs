@@ -0,0 +1,119 @@
+use anyhow::{Result, ensure};
+
+use crate::model_table::ModelTable;
+
+/// Inclusive range of values accepted by a stove parameter.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Limit {
+    pub min: f32,
+    pub max: f32,
+    /// Values must be a multiple of this step
+    pub step: f32,
+}
+
+impl Limit {
+    pub const fn new(min: f32, max: f32, step: f32) -> Self {
+        Limit { min, max, step }
+    }
+
+    pub fn contains(&self, value: f32) -> bool {
+        (self.min..=self.max).contains(&value) && self.is_step_multiple(value)
+    }
+
+    fn is_step_multiple(&self, value: f32) -> bool {
+        let steps = value / self.step;
+        (steps - steps.round()).abs() < 1e-3
+    }
+
+    /// Checks the value, errors read like `Heating power must be 0 <= power <= 100 but it was 101`.
+    pub fn ensure(&self, label: &str, symbol: &str, unit: &str, value: f32) -> Result<()> {
+        ensure!(
+            (self.min..=self.max).contains(&value),
+            "{label} must be {} <= {symbol} <= {}{unit} but it was {value}",
+            self.min,
+            self.max
+        );
+        ensure!(
+            self.is_step_multiple(value),
+            "{label} must be a multiple of {}{unit} but it was {value}",
+            self.step
+        );
+        Ok(())
+    }
+}
+
+/// Parameter limits of a stove model.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StoveLimits {
+    pub heating_power: Limit,
+    pub idle_temperature: Limit,
+    pub target_temperature: Limit,
+    pub frost_protection_temperature: Limit,
+    pub temperature_offset: Limit,
+    pub bake_temperature: Limit,
+    pub convection_fan_level: Limit,
+    pub convection_fan_area: Limit,
+}
+
+impl Default for StoveLimits {
+    /// Limits shared by most Rika stoves, as displayed by rika-firenet.com.
+    fn default() -> Self {
+        StoveLimits {
+            heating_power: Limit::new(0.0, 100.0, 1.0),
+            idle_temperature: Limit::new(12.0, 20.0, 1.0),
+            target_temperature: Limit::new(14.0, 28.0, 1.0),
+            frost_protection_temperature: Limit::new(4.0, 10.0, 1.0),
+            temperature_offset: Limit::new(-4.0, 4.0, 0.5),
+            bake_temperature: Limit::new(100.0, 250.0, 1.0),
+            convection_fan_level: Limit::new(0.0, 5.0, 1.0),
+            convection_fan_area: Limit::new(-30.0, 30.0, 1.0),
+        }
+    }
+}
+
+/// Limits by manufacturer and model, models missing from the table use the default limits.
+pub type LimitsTable = ModelTable<StoveLimits>;
+
+#[cfg(test)]
+mod tests {
+    use crate::limits::{Limit, LimitsTable, StoveLimits};
+
+    #[test]
+    fn can_check_inclusive_bounds_and_steps() {
+        let limit = Limit::new(-4.0, 4.0, 0.5);
+        assert!(limit.contains(-4.0));
+        assert!(limit.contains(4.0));
+        assert!(limit.contains(1.5));
+        assert!(!limit.contains(1.2));
+        assert!(!limit.contains(4.5));
+
+        assert_eq!(
+            limit
+                .ensure("Temperature offset", "offset", "°C", 1.2)
+                .unwrap_err()
+                .to_string(),
+            "Temperature offset must be a multiple of 0.5°C but it was 1.2"
+        );
+    }
+
+    #[test]
+    fn can_lookup_model_limits() {
+        let table = LimitsTable::default().with_model(
+            "RIKA",
+            "DOMO",
+            StoveLimits {
+                target_temperature: Limit::new(10.0, 30.0, 1.0),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            table.get("RIKA", "DOMO").target_temperature,
+            Limit::new(10.0, 30.0, 1.0)
+        );
+        assert_eq!(
+            table.get("RIKA", "PARO").target_temperature,
+            Limit::new(14.0, 28.0, 1.0)
+        );
+    }
+}
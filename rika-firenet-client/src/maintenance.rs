@@ -0,0 +1,163 @@
+use std::fmt::{self, Display};
+
+use rika_firenet_openapi::models::StoveStatus;
+use serde::{Deserialize, Serialize};
+
+/// Pellet weight, negative when a countdown went past its deadline.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Kilograms(pub i32);
+
+impl Display for Kilograms {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} kg", self.0)
+    }
+}
+
+/// Burning time, negative when a countdown went past its deadline.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Hours(pub i32);
+
+impl Display for Hours {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} h", self.0)
+    }
+}
+
+/// Remaining amounts below which maintenance is reported as due soon.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaintenanceThresholds {
+    pub service_kg: Kilograms,
+    pub service_hours: Hours,
+    pub cleaning_kg: Kilograms,
+}
+
+impl Default for MaintenanceThresholds {
+    fn default() -> Self {
+        MaintenanceThresholds {
+            service_kg: Kilograms(100),
+            service_hours: Hours(50),
+            cleaning_kg: Kilograms(50),
+        }
+    }
+}
+
+/// Service and cleaning state of a stove.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct MaintenanceReport {
+    /// Pellets to burn until the next service
+    pub service_remaining_kg: Kilograms,
+    /// Burning time until the next service, 0 when it is due now
+    pub service_remaining_hours: Hours,
+    /// Pellets to burn until the next cleaning
+    pub cleaning_remaining_kg: Kilograms,
+    /// Pellets burnt between two big cleanings
+    pub big_cleaning_interval_kg: Kilograms,
+    /// Raw service request code displayed by the stove, from `StoveSensors.status_service`
+    ///
+    /// The meaning of the codes isn't documented, so they don't affect `overdue`.
+    pub service_request: Option<i32>,
+    /// A countdown ran out
+    pub overdue: bool,
+    /// A countdown is below its threshold or a service request is displayed, but nothing is
+    /// overdue yet
+    pub due_soon: bool,
+}
+
+impl MaintenanceReport {
+    pub fn new(status: &StoveStatus, thresholds: &MaintenanceThresholds) -> Self {
+        let sensors = &status.sensors;
+        let service_remaining_kg = Kilograms(sensors.parameter_service_countdown_kg);
+        let service_remaining_hours = Hours(sensors.parameter_service_countdown_time);
+        let cleaning_remaining_kg = Kilograms(sensors.parameter_kg_till_cleaning);
+        let service_request = Some(sensors.status_service).filter(|code| *code != 0);
+
+        let overdue = service_remaining_kg.0 <= 0
+            || service_remaining_hours.0 <= 0
+            || cleaning_remaining_kg.0 <= 0;
+        let due_soon = !overdue
+            && (service_request.is_some()
+                || service_remaining_kg <= thresholds.service_kg
+                || service_remaining_hours <= thresholds.service_hours
+                || cleaning_remaining_kg <= thresholds.cleaning_kg);
+
+        MaintenanceReport {
+            service_remaining_kg,
+            service_remaining_hours,
+            cleaning_remaining_kg,
+            big_cleaning_interval_kg: Kilograms(sensors.parameter_clean_interval_big),
+            service_request,
+            overdue,
+            due_soon,
+        }
+    }
+}
+
+impl From<&StoveStatus> for MaintenanceReport {
+    /// Uses the default thresholds.
+    fn from(status: &StoveStatus) -> Self {
+        MaintenanceReport::new(status, &MaintenanceThresholds::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rika_firenet_openapi::models::StoveStatus;
+
+    use crate::maintenance::{Hours, Kilograms, MaintenanceReport, MaintenanceThresholds};
+
+    fn mock_status() -> StoveStatus {
+        let mut status = crate::tests::mock_status("__stove_id__");
+        status.sensors.parameter_service_countdown_time = 600;
+        status
+    }
+
+    #[test]
+    fn can_report_maintenance_state() {
+        let report = MaintenanceReport::from(&mock_status());
+
+        assert_eq!(report.service_remaining_kg, Kilograms(368));
+        assert_eq!(report.service_remaining_hours, Hours(600));
+        assert_eq!(report.cleaning_remaining_kg, Kilograms(1500));
+        assert_eq!(report.big_cleaning_interval_kg, Kilograms(360));
+        assert_eq!(report.service_request, None);
+        assert!(!report.overdue);
+        assert!(!report.due_soon);
+    }
+
+    #[test]
+    fn can_tell_when_maintenance_is_due() {
+        let mut status = mock_status();
+        let thresholds = MaintenanceThresholds {
+            service_kg: Kilograms(400),
+            ..Default::default()
+        };
+        assert!(MaintenanceReport::new(&status, &thresholds).due_soon);
+
+        status.sensors.parameter_service_countdown_time = 30;
+        let report = MaintenanceReport::from(&status);
+        assert_eq!(report.service_remaining_hours, Hours(30));
+        assert!(report.due_soon);
+
+        status.sensors.parameter_service_countdown_time = 0;
+        let report = MaintenanceReport::from(&status);
+        assert!(report.overdue, "no service hours left means due now");
+        status.sensors.parameter_service_countdown_time = 30;
+
+        status.sensors.parameter_kg_till_cleaning = -5;
+        let report = MaintenanceReport::from(&status);
+        assert!(report.overdue);
+        assert!(!report.due_soon);
+
+        let mut status = mock_status();
+        status.sensors.status_service = 2;
+        let report = MaintenanceReport::from(&status);
+        assert!(
+            !report.overdue,
+            "service requests alone aren't known to be overdue"
+        );
+        assert!(report.due_soon);
+        assert_eq!(report.service_request, Some(2));
+    }
+}
@@ -1,10 +1,11 @@
+use std::fmt::{self, Display};
 use std::str::FromStr;
 
 use anyhow::{Result, bail, ensure};
-use rika_firenet_openapi::models::StoveControls;
+use rika_firenet_openapi::models::{StoveControls, Temperature};
 use serde::{Deserialize, Serialize};
 
-#[derive(PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
 pub enum StatusDetail {
     Baking,
     BurnOff,
@@ -23,6 +24,80 @@ pub enum StatusDetail {
     SplitLogCheck,
 }
 
+impl StatusDetail {
+    pub const ALL: [StatusDetail; 15] = [
+        StatusDetail::Baking,
+        StatusDetail::BurnOff,
+        StatusDetail::Cleaning,
+        StatusDetail::Running,
+        StatusDetail::DeepCleaning,
+        StatusDetail::ExternalRequest,
+        StatusDetail::FrostProtection,
+        StatusDetail::HeatingUp,
+        StatusDetail::Ignition,
+        StatusDetail::Off,
+        StatusDetail::Standby,
+        StatusDetail::Startup,
+        StatusDetail::Unknown,
+        StatusDetail::SplitLogMode,
+        StatusDetail::SplitLogCheck,
+    ];
+}
+
+/// A [`StatusDetail`] along with the stove values it was computed from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StatusInterpretation {
+    pub detail: StatusDetail,
+    pub main_state: u8,
+    pub sub_state: u8,
+    pub frost_started: bool,
+    pub bake_inputs: BakeModeInputs,
+    /// Why the detail is [`StatusDetail::Unknown`], e.g. `unknown substate 7 for main state 1`
+    pub unknown_reason: Option<String>,
+}
+
+impl StatusInterpretation {
+    /// Fails with the fallback reason when the state couldn't be interpreted.
+    pub fn into_result(self) -> Result<StatusDetail> {
+        match self.unknown_reason {
+            Some(reason) => bail!(
+                "Can't interpret stove state (main state {}, substate {}): {reason}",
+                self.main_state,
+                self.sub_state
+            ),
+            None => Ok(self.detail),
+        }
+    }
+}
+
+/// Values deciding whether a running stove is baking.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BakeModeInputs {
+    pub operating_mode: Option<u8>,
+    pub bake_temperature: Option<Temperature>,
+    pub input_bake_temperature: Option<Temperature>,
+}
+
+impl BakeModeInputs {
+    pub fn is_bake_mode(&self) -> bool {
+        self.temperature_difference().is_some()
+    }
+
+    /// Distance between the oven temperature and the bake temperature, `None` unless baking.
+    pub fn temperature_difference(&self) -> Option<f32> {
+        match (
+            self.operating_mode,
+            self.bake_temperature,
+            self.input_bake_temperature,
+        ) {
+            (Some(3), Some(target), Some(current)) => {
+                Some((current.celsius() - target.celsius()).abs())
+            }
+            _ => None,
+        }
+    }
+}
+
 pub enum StoveControl {
     OperatingMode(OperatingMode),
     HeatingPower(i32),
@@ -34,14 +109,16 @@ pub enum OperatingMode {
     Manual,
     Auto,
     Comfort,
+    Bake,
 }
 
-impl Into<u8> for OperatingMode {
-    fn into(self) -> u8 {
-        match self {
+impl From<OperatingMode> for u8 {
+    fn from(mode: OperatingMode) -> Self {
+        match mode {
             OperatingMode::Manual => 0,
             OperatingMode::Auto => 1,
             OperatingMode::Comfort => 2,
+            OperatingMode::Bake => 3,
         }
     }
 }
@@ -52,11 +129,70 @@ impl OperatingMode {
             0 => Ok(OperatingMode::Manual),
             1 => Ok(OperatingMode::Auto),
             2 => Ok(OperatingMode::Comfort),
+            3 => Ok(OperatingMode::Bake),
             num => bail!("{num} is not a valid OperatingMode"),
         }
     }
 }
 
+/// Power level requested by an external thermostat, Firenet values 1 to 4.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum RoomPowerRequest {
+    Low,
+    Medium,
+    High,
+    Maximum,
+}
+
+impl From<RoomPowerRequest> for u8 {
+    fn from(request: RoomPowerRequest) -> Self {
+        match request {
+            RoomPowerRequest::Low => 1,
+            RoomPowerRequest::Medium => 2,
+            RoomPowerRequest::High => 3,
+            RoomPowerRequest::Maximum => 4,
+        }
+    }
+}
+
+impl RoomPowerRequest {
+    pub fn parse(level: u8) -> Result<Self> {
+        match level {
+            1 => Ok(RoomPowerRequest::Low),
+            2 => Ok(RoomPowerRequest::Medium),
+            3 => Ok(RoomPowerRequest::High),
+            4 => Ok(RoomPowerRequest::Maximum),
+            num => bail!("{num} is not a valid RoomPowerRequest"),
+        }
+    }
+}
+
+/// One of the MultiAir convection fans.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ConvectionFan {
+    First,
+    Second,
+}
+
+impl Display for ConvectionFan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConvectionFan::First => f.write_str("convection fan 1"),
+            ConvectionFan::Second => f.write_str("convection fan 2"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+/// Settings of a MultiAir fan, checked against the stove [`crate::limits::StoveLimits`] when set.
+pub struct ConvectionFanSettings {
+    pub active: bool,
+    /// Fan speed, usually from 0 to 5
+    pub level: u8,
+    /// Heat distribution between the front and the ducts, usually from -30 to 30
+    pub area: i8,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct HeatingSchedule {
     pub monday: DailySchedule,
@@ -92,15 +228,210 @@ impl HeatingSchedule {
             sunday: week_end,
         }
     }
+
+    pub fn day(&self, weekday: Weekday) -> &DailySchedule {
+        match weekday {
+            Weekday::Monday => &self.monday,
+            Weekday::Tuesday => &self.tuesday,
+            Weekday::Wednesday => &self.wednesday,
+            Weekday::Thursday => &self.thursday,
+            Weekday::Friday => &self.friday,
+            Weekday::Saturday => &self.saturday,
+            Weekday::Sunday => &self.sunday,
+        }
+    }
+
+    pub fn day_mut(&mut self, weekday: Weekday) -> &mut DailySchedule {
+        match weekday {
+            Weekday::Monday => &mut self.monday,
+            Weekday::Tuesday => &mut self.tuesday,
+            Weekday::Wednesday => &mut self.wednesday,
+            Weekday::Thursday => &mut self.thursday,
+            Weekday::Friday => &mut self.friday,
+            Weekday::Saturday => &mut self.saturday,
+            Weekday::Sunday => &mut self.sunday,
+        }
+    }
+
+    /// Lists every issue found in the daily schedules, see [`DailySchedule::validate`].
+    pub fn validate(&self) -> Vec<(Weekday, ScheduleIssue)> {
+        Weekday::ALL
+            .into_iter()
+            .flat_map(|weekday| {
+                self.day(weekday)
+                    .validate()
+                    .into_iter()
+                    .map(move |issue| (weekday, issue))
+            })
+            .collect()
+    }
+
+    pub fn ensure_valid(&self) -> Result<()> {
+        ensure_no_issues(self.validate())
+    }
+
+    pub fn ensure_valid_day(&self, weekday: Weekday) -> Result<()> {
+        ensure_no_issues(
+            self.validate()
+                .into_iter()
+                .filter(|(day, _)| *day == weekday)
+                .collect(),
+        )
+    }
+
+    pub fn normalize(&self) -> Self {
+        HeatingSchedule {
+            monday: self.monday.normalize(),
+            tuesday: self.tuesday.normalize(),
+            wednesday: self.wednesday.normalize(),
+            thursday: self.thursday.normalize(),
+            friday: self.friday.normalize(),
+            saturday: self.saturday.normalize(),
+            sunday: self.sunday.normalize(),
+        }
+    }
+
+    pub fn is_active_at(&self, weekday: Weekday, time: &HeatTime) -> bool {
+        self.day(weekday).is_active_at(time)
+    }
+
+    /// Finds the first time strictly after the given one when the stove switches on or off,
+    /// looking up to one week ahead. Returns `None` when the schedule never heats.
+    pub fn next_transition(
+        &self,
+        weekday: Weekday,
+        after: &HeatTime,
+    ) -> Option<ScheduleTransition> {
+        let normalized = self.normalize();
+        (0..=7)
+            .flat_map(|offset| {
+                let day = weekday.plus_days(offset);
+                normalized
+                    .day(day)
+                    .transitions()
+                    .into_iter()
+                    .filter(move |(time, _)| match offset {
+                        0 => time > after,
+                        7 => time <= after,
+                        _ => true,
+                    })
+                    .map(move |(time, heating)| ScheduleTransition {
+                        weekday: day,
+                        time,
+                        heating,
+                    })
+            })
+            .next()
+    }
+
+    pub fn active_minutes_per_week(&self) -> u32 {
+        Weekday::ALL
+            .into_iter()
+            .map(|weekday| u32::from(self.day(weekday).active_minutes()))
+            .sum()
+    }
+}
+
+fn ensure_no_issues(issues: Vec<(Weekday, ScheduleIssue)>) -> Result<()> {
+    ensure!(
+        issues.is_empty(),
+        "Invalid heating schedule: {}",
+        issues
+            .iter()
+            .map(|(weekday, issue)| format!("{weekday}: {issue}"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    Ok(())
+}
+
+/// A point in the week when the stove switches heating on or off.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScheduleTransition {
+    pub weekday: Weekday,
+    pub time: HeatTime,
+    /// `true` when heating starts, `false` when it stops
+    pub heating: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl Weekday {
+    pub const ALL: [Weekday; 7] = [
+        Weekday::Monday,
+        Weekday::Tuesday,
+        Weekday::Wednesday,
+        Weekday::Thursday,
+        Weekday::Friday,
+        Weekday::Saturday,
+        Weekday::Sunday,
+    ];
+
+    pub fn plus_days(self, days: usize) -> Self {
+        Weekday::ALL[(self as usize + days) % 7]
+    }
+}
+
+impl Display for Weekday {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Weekday::Monday => "monday",
+            Weekday::Tuesday => "tuesday",
+            Weekday::Wednesday => "wednesday",
+            Weekday::Thursday => "thursday",
+            Weekday::Friday => "friday",
+            Weekday::Saturday => "saturday",
+            Weekday::Sunday => "sunday",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScheduleIssue {
+    /// The period begins and ends at the same time but isn't the `00000000` "unused" value
+    ZeroLengthPeriod(HeatPeriod),
+    /// The period ends before it begins
+    ReversedPeriod(HeatPeriod),
+    /// The second period begins before the first one
+    UnorderedPeriods,
+    /// Both periods share some time
+    OverlappingPeriods,
+}
+
+impl Display for ScheduleIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScheduleIssue::ZeroLengthPeriod(period) => {
+                write!(f, "period {period} has a zero length")
+            }
+            ScheduleIssue::ReversedPeriod(period) => {
+                write!(f, "period {period} ends before it begins")
+            }
+            ScheduleIssue::UnorderedPeriods => {
+                f.write_str("second period begins before the first one")
+            }
+            ScheduleIssue::OverlappingPeriods => f.write_str("periods overlap"),
+        }
+    }
 }
 
 impl From<StoveControls> for HeatingSchedule {
     fn from(controls: StoveControls) -> Self {
         HeatingSchedule {
             monday: DailySchedule::from(controls.heating_time_mon1, controls.heating_time_mon2),
-            tuesday: DailySchedule::from(controls.heating_time_thu1, controls.heating_time_thu2),
+            tuesday: DailySchedule::from(controls.heating_time_tue1, controls.heating_time_tue2),
             wednesday: DailySchedule::from(controls.heating_time_wed1, controls.heating_time_wed2),
-            thursday: DailySchedule::from(controls.heating_time_tue1, controls.heating_time_tue2),
+            thursday: DailySchedule::from(controls.heating_time_thu1, controls.heating_time_thu2),
             friday: DailySchedule::from(controls.heating_time_fri1, controls.heating_time_fri2),
             saturday: DailySchedule::from(controls.heating_time_sat1, controls.heating_time_sat2),
             sunday: DailySchedule::from(controls.heating_time_sun1, controls.heating_time_sun2),
@@ -146,9 +477,89 @@ impl DailySchedule {
                 .unwrap_or_default(),
         }
     }
+
+    /// First period, `None` when it holds the `00000000` "unused" value.
+    pub fn first_period(&self) -> Option<&HeatPeriod> {
+        Some(&self.first).filter(|period| !period.is_unused())
+    }
+
+    /// Second period, `None` when it holds the `00000000` "unused" value.
+    pub fn second_period(&self) -> Option<&HeatPeriod> {
+        Some(&self.second).filter(|period| !period.is_unused())
+    }
+
+    pub fn validate(&self) -> Vec<ScheduleIssue> {
+        let mut issues = Vec::new();
+        for period in [self.first_period(), self.second_period()]
+            .into_iter()
+            .flatten()
+        {
+            if period.begin == period.end {
+                issues.push(ScheduleIssue::ZeroLengthPeriod(period.clone()));
+            } else if period.end < period.begin {
+                issues.push(ScheduleIssue::ReversedPeriod(period.clone()));
+            }
+        }
+        if let (Some(first), Some(second)) = (self.first_period(), self.second_period()) {
+            if second.begin < first.begin {
+                issues.push(ScheduleIssue::UnorderedPeriods);
+            }
+            if first.begin < second.end && second.begin < first.end {
+                issues.push(ScheduleIssue::OverlappingPeriods);
+            }
+        }
+        issues
+    }
+
+    /// Sorts periods and merges the ones overlapping or touching each other.
+    ///
+    /// Zero length and reversed periods don't heat at all, they are dropped.
+    pub fn normalize(&self) -> Self {
+        let mut periods: Vec<HeatPeriod> = [self.first_period(), self.second_period()]
+            .into_iter()
+            .flatten()
+            .filter(|period| period.begin < period.end)
+            .cloned()
+            .collect();
+        periods.sort_by(|a, b| a.begin.cmp(&b.begin));
+        if let [first, second] = periods.as_slice()
+            && second.begin <= first.end
+        {
+            periods = vec![HeatPeriod {
+                begin: first.begin.clone(),
+                end: first.end.clone().max(second.end.clone()),
+            }];
+        }
+        let mut periods = periods.into_iter();
+        DailySchedule::new(periods.next().unwrap_or_default(), periods.next())
+    }
+
+    pub fn is_active_at(&self, time: &HeatTime) -> bool {
+        [self.first_period(), self.second_period()]
+            .into_iter()
+            .flatten()
+            .any(|period| period.contains(time))
+    }
+
+    pub fn active_minutes(&self) -> u16 {
+        let normalized = self.normalize();
+        [normalized.first_period(), normalized.second_period()]
+            .into_iter()
+            .flatten()
+            .map(HeatPeriod::duration_minutes)
+            .sum()
+    }
+
+    fn transitions(&self) -> Vec<(HeatTime, bool)> {
+        [self.first_period(), self.second_period()]
+            .into_iter()
+            .flatten()
+            .flat_map(|period| [(period.begin.clone(), true), (period.end.clone(), false)])
+            .collect()
+    }
 }
 
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub struct HeatPeriod {
     begin: HeatTime,
     end: HeatTime,
@@ -175,13 +586,48 @@ impl HeatPeriod {
             },
         })
     }
+
+    pub fn begin(&self) -> &HeatTime {
+        &self.begin
+    }
+
+    pub fn end(&self) -> &HeatTime {
+        &self.end
+    }
+
+    /// Firenet uses `00000000` for a period which isn't used.
+    pub fn is_unused(&self) -> bool {
+        *self == HeatPeriod::default()
+    }
+
+    /// Whether the stove heats at the given time, the end time being excluded.
+    pub fn contains(&self, time: &HeatTime) -> bool {
+        self.begin <= *time && *time < self.end
+    }
+
+    /// Heating duration, zero for unused or reversed periods.
+    pub fn duration_minutes(&self) -> u16 {
+        self.end
+            .minutes_of_day()
+            .saturating_sub(self.begin.minutes_of_day())
+    }
 }
 
-impl Into<String> for HeatPeriod {
-    fn into(self) -> String {
+impl Display for HeatPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:0>2}:{:0>2}-{:0>2}:{:0>2}",
+            self.begin.hours, self.begin.minutes, self.end.hours, self.end.minutes
+        )
+    }
+}
+
+impl From<HeatPeriod> for String {
+    fn from(period: HeatPeriod) -> Self {
         format!(
             "{:0>2}{:0>2}{:0>2}{:0>2}",
-            self.begin.hours, self.begin.minutes, self.end.hours, self.end.minutes
+            period.begin.hours, period.begin.minutes, period.end.hours, period.end.minutes
         )
     }
 }
@@ -198,7 +644,7 @@ impl FromStr for HeatPeriod {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub struct HeatTime {
     hours: u8,
     minutes: u8,
@@ -210,6 +656,18 @@ impl HeatTime {
         ensure!(minutes <= 59, "minutes must be 0 <= hh <= 59");
         Ok(HeatTime { hours, minutes })
     }
+
+    pub fn hours(&self) -> u8 {
+        self.hours
+    }
+
+    pub fn minutes(&self) -> u8 {
+        self.minutes
+    }
+
+    pub fn minutes_of_day(&self) -> u16 {
+        u16::from(self.hours) * 60 + u16::from(self.minutes)
+    }
 }
 
 impl FromStr for HeatTime {
@@ -221,20 +679,14 @@ impl FromStr for HeatTime {
     }
 }
 
-impl Default for HeatTime {
-    fn default() -> Self {
-        HeatTime {
-            hours: 0,
-            minutes: 0,
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use rika_firenet_openapi::models::StoveControls;
 
-    use crate::model::{DailySchedule, HeatPeriod, HeatingSchedule};
+    use crate::model::{
+        DailySchedule, HeatPeriod, HeatTime, HeatingSchedule, ScheduleIssue, ScheduleTransition,
+        Weekday,
+    };
 
     #[test]
     fn can_parse_missing_schedule() {
@@ -326,16 +778,16 @@ mod tests {
                 HeatPeriod::new(18, 45, 22, 00).unwrap(),
             ),
             tuesday: DailySchedule::dual(
-                HeatPeriod::new(2, 0, 3, 30).unwrap(),
-                HeatPeriod::new(8, 0, 23, 30).unwrap(),
+                HeatPeriod::new(6, 1, 7, 2).unwrap(),
+                HeatPeriod::new(8, 3, 9, 4).unwrap(),
             ),
             wednesday: DailySchedule::dual(
                 HeatPeriod::new(7, 0, 11, 15).unwrap(),
                 HeatPeriod::new(16, 30, 19, 45).unwrap(),
             ),
             thursday: DailySchedule::dual(
-                HeatPeriod::new(6, 1, 7, 2).unwrap(),
-                HeatPeriod::new(8, 3, 9, 4).unwrap(),
+                HeatPeriod::new(2, 0, 3, 30).unwrap(),
+                HeatPeriod::new(8, 0, 23, 30).unwrap(),
             ),
             friday: DailySchedule::dual(
                 HeatPeriod::new(10, 10, 12, 12).unwrap(),
@@ -361,4 +813,182 @@ mod tests {
         let error = HeatPeriod::new(18, 30, 18, 29).unwrap_err();
         assert_eq!(error.to_string(), "Heat period can't overlap 2 days");
     }
+
+    #[test]
+    fn can_validate_schedule() {
+        let valid = DailySchedule::dual(
+            HeatPeriod::new(7, 0, 10, 0).unwrap(),
+            HeatPeriod::new(10, 0, 22, 0).unwrap(),
+        );
+        assert_eq!(valid.validate(), vec![]);
+        assert_eq!(DailySchedule::default().validate(), vec![]);
+
+        let unordered = DailySchedule::dual(
+            HeatPeriod::new(18, 0, 22, 0).unwrap(),
+            HeatPeriod::new(7, 0, 10, 0).unwrap(),
+        );
+        assert_eq!(unordered.validate(), vec![ScheduleIssue::UnorderedPeriods]);
+
+        let overlapping = DailySchedule::dual(
+            HeatPeriod::new(7, 0, 12, 0).unwrap(),
+            HeatPeriod::new(11, 0, 22, 0).unwrap(),
+        );
+        assert_eq!(
+            overlapping.validate(),
+            vec![ScheduleIssue::OverlappingPeriods]
+        );
+
+        let broken = DailySchedule::dual("08000800".parse().unwrap(), "12000900".parse().unwrap());
+        assert_eq!(
+            broken.validate(),
+            vec![
+                ScheduleIssue::ZeroLengthPeriod("08000800".parse().unwrap()),
+                ScheduleIssue::ReversedPeriod("12000900".parse().unwrap()),
+            ]
+        );
+    }
+
+    #[test]
+    fn can_report_invalid_week_days() {
+        let mut schedule =
+            HeatingSchedule::all_same(DailySchedule::single(HeatPeriod::new(7, 0, 22, 0).unwrap()));
+        schedule.wednesday = DailySchedule::dual(
+            HeatPeriod::new(7, 0, 12, 0).unwrap(),
+            HeatPeriod::new(6, 0, 8, 0).unwrap(),
+        );
+
+        assert_eq!(
+            schedule.validate(),
+            vec![
+                (Weekday::Wednesday, ScheduleIssue::UnorderedPeriods),
+                (Weekday::Wednesday, ScheduleIssue::OverlappingPeriods),
+            ]
+        );
+        assert_eq!(
+            schedule.ensure_valid().unwrap_err().to_string(),
+            "Invalid heating schedule: wednesday: second period begins before the first one, wednesday: periods overlap"
+        );
+    }
+
+    #[test]
+    fn can_normalize_schedule() {
+        let unordered = DailySchedule::dual(
+            HeatPeriod::new(18, 0, 22, 0).unwrap(),
+            HeatPeriod::new(7, 0, 10, 0).unwrap(),
+        );
+        assert_eq!(
+            unordered.normalize(),
+            DailySchedule::dual(
+                HeatPeriod::new(7, 0, 10, 0).unwrap(),
+                HeatPeriod::new(18, 0, 22, 0).unwrap(),
+            )
+        );
+
+        let overlapping = DailySchedule::dual(
+            HeatPeriod::new(11, 0, 22, 0).unwrap(),
+            HeatPeriod::new(7, 0, 12, 0).unwrap(),
+        );
+        assert_eq!(
+            overlapping.normalize(),
+            DailySchedule::single(HeatPeriod::new(7, 0, 22, 0).unwrap())
+        );
+
+        let second_only = DailySchedule::dual(
+            "08000800".parse().unwrap(),
+            HeatPeriod::new(7, 0, 12, 0).unwrap(),
+        );
+        let normalized = second_only.normalize();
+        assert_eq!(
+            normalized.first_period(),
+            Some(&HeatPeriod::new(7, 0, 12, 0).unwrap())
+        );
+        assert_eq!(normalized.second_period(), None);
+    }
+
+    fn time(hours: u8, minutes: u8) -> HeatTime {
+        HeatTime::new(hours, minutes).unwrap()
+    }
+
+    #[test]
+    fn can_tell_if_schedule_is_active() {
+        let schedule = HeatingSchedule::week_vs_end_days(
+            DailySchedule::dual(
+                HeatPeriod::new(7, 0, 10, 0).unwrap(),
+                HeatPeriod::new(18, 0, 22, 0).unwrap(),
+            ),
+            DailySchedule::single(HeatPeriod::new(10, 15, 23, 0).unwrap()),
+        );
+
+        assert!(!schedule.is_active_at(Weekday::Monday, &time(6, 59)));
+        assert!(schedule.is_active_at(Weekday::Monday, &time(7, 0)));
+        assert!(schedule.is_active_at(Weekday::Monday, &time(9, 59)));
+        assert!(!schedule.is_active_at(Weekday::Monday, &time(10, 0)));
+        assert!(schedule.is_active_at(Weekday::Friday, &time(21, 30)));
+        assert!(!schedule.is_active_at(Weekday::Saturday, &time(7, 30)));
+        assert!(schedule.is_active_at(Weekday::Sunday, &time(22, 59)));
+        assert!(
+            !HeatingSchedule::all_same(DailySchedule::default())
+                .is_active_at(Weekday::Monday, &time(0, 0))
+        );
+    }
+
+    #[test]
+    fn can_find_next_schedule_transition() {
+        let schedule = HeatingSchedule::week_vs_end_days(
+            DailySchedule::dual(
+                HeatPeriod::new(7, 0, 10, 0).unwrap(),
+                HeatPeriod::new(18, 0, 22, 0).unwrap(),
+            ),
+            DailySchedule::default(),
+        );
+
+        assert_eq!(
+            schedule.next_transition(Weekday::Monday, &time(7, 0)),
+            Some(ScheduleTransition {
+                weekday: Weekday::Monday,
+                time: time(10, 0),
+                heating: false,
+            })
+        );
+        assert_eq!(
+            schedule.next_transition(Weekday::Friday, &time(22, 0)),
+            Some(ScheduleTransition {
+                weekday: Weekday::Monday,
+                time: time(7, 0),
+                heating: true,
+            })
+        );
+
+        let once_a_week = HeatingSchedule {
+            wednesday: DailySchedule::single(HeatPeriod::new(8, 0, 9, 0).unwrap()),
+            ..HeatingSchedule::all_same(DailySchedule::default())
+        };
+        assert_eq!(
+            once_a_week.next_transition(Weekday::Wednesday, &time(9, 0)),
+            Some(ScheduleTransition {
+                weekday: Weekday::Wednesday,
+                time: time(8, 0),
+                heating: true,
+            })
+        );
+        assert_eq!(
+            HeatingSchedule::all_same(DailySchedule::default())
+                .next_transition(Weekday::Monday, &time(0, 0)),
+            None
+        );
+    }
+
+    #[test]
+    fn can_count_active_minutes_per_week() {
+        let schedule = HeatingSchedule::week_vs_end_days(
+            DailySchedule::dual(
+                HeatPeriod::new(7, 30, 10, 0).unwrap(),
+                HeatPeriod::new(9, 0, 11, 0).unwrap(),
+            ),
+            DailySchedule::single(HeatPeriod::new(10, 0, 20, 0).unwrap()),
+        );
+
+        assert_eq!(schedule.monday.active_minutes(), 210);
+        assert_eq!(schedule.active_minutes_per_week(), 5 * 210 + 2 * 600);
+    }
 }
@@ -0,0 +1,50 @@
+use std::collections::HashMap;
+
+/// Settings by manufacturer and model, see `StoveStatus.oem` and `StoveStatus.stove_type`.
+///
+/// Models missing from the table use the default settings.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ModelTable<T> {
+    default: T,
+    models: HashMap<(String, String), T>,
+}
+
+impl<T> ModelTable<T> {
+    pub fn new(default: T) -> Self {
+        ModelTable {
+            default,
+            models: HashMap::new(),
+        }
+    }
+
+    pub fn with_model(
+        mut self,
+        oem: impl Into<String>,
+        stove_type: impl Into<String>,
+        settings: T,
+    ) -> Self {
+        self.models
+            .insert((oem.into(), stove_type.into()), settings);
+        self
+    }
+
+    pub fn get(&self, oem: &str, stove_type: &str) -> &T {
+        self.models
+            .get(&(oem.to_string(), stove_type.to_string()))
+            .unwrap_or(&self.default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model_table::ModelTable;
+
+    #[test]
+    fn can_fall_back_to_the_default_settings() {
+        let table = ModelTable::new(1).with_model("RIKA", "DOMO", 2);
+
+        assert_eq!(table.get("RIKA", "DOMO"), &2);
+        assert_eq!(table.get("RIKA", "PARO"), &1);
+        assert_eq!(table.get("OTHER", "DOMO"), &1);
+    }
+}
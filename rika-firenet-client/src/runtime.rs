@@ -0,0 +1,139 @@
+use std::time::{Duration, SystemTime};
+
+use rika_firenet_openapi::models::StoveStatus;
+use serde::{Deserialize, Serialize};
+
+use crate::consumption::DAY;
+
+/// Lifetime runtime counters of a stove at a given time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuntimeSnapshot {
+    pub at: SystemTime,
+    /// `StoveSensors.parameter_runtime_pellets`
+    pub pellet_hours: i32,
+    /// `StoveSensors.parameter_runtime_logs`, only counted by `StoveFeatures.log_runtime` stoves
+    pub log_hours: i32,
+    pub ignitions: i32,
+    pub on_off_cycles: i32,
+    /// `StoveFeatures.log_runtime`
+    pub counts_log_runtime: bool,
+}
+
+impl RuntimeSnapshot {
+    pub fn new(at: SystemTime, status: &StoveStatus) -> Self {
+        let sensors = &status.sensors;
+        RuntimeSnapshot {
+            at,
+            pellet_hours: sensors.parameter_runtime_pellets,
+            log_hours: sensors.parameter_runtime_logs,
+            ignitions: sensors.parameter_ignition_count,
+            on_off_cycles: sensors.parameter_on_off_cycle_count,
+            counts_log_runtime: status.stove_features.log_runtime,
+        }
+    }
+}
+
+/// Runtime metrics between two snapshots of the same stove.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RuntimeStats {
+    pub period: Duration,
+    pub pellet_hours: u32,
+    /// `None` for stoves not counting log runtime
+    pub log_hours: Option<u32>,
+    pub ignitions: u32,
+    pub on_off_cycles: u32,
+}
+
+impl RuntimeStats {
+    /// Counters going down, e.g. after a main board replacement, count as unchanged.
+    pub fn between(first: &RuntimeSnapshot, last: &RuntimeSnapshot) -> Self {
+        let delta = |first: i32, last: i32| (last - first).max(0).unsigned_abs();
+        RuntimeStats {
+            period: last.at.duration_since(first.at).unwrap_or_default(),
+            pellet_hours: delta(first.pellet_hours, last.pellet_hours),
+            log_hours: last
+                .counts_log_runtime
+                .then(|| delta(first.log_hours, last.log_hours)),
+            ignitions: delta(first.ignitions, last.ignitions),
+            on_off_cycles: delta(first.on_off_cycles, last.on_off_cycles),
+        }
+    }
+
+    pub fn hours_burned(&self) -> u32 {
+        self.pellet_hours + self.log_hours.unwrap_or(0)
+    }
+
+    pub fn ignitions_per_day(&self) -> f64 {
+        if self.period.is_zero() {
+            return 0.0;
+        }
+        f64::from(self.ignitions) * DAY.as_secs_f64() / self.period.as_secs_f64()
+    }
+
+    /// Average pellet burning time between two ignitions, `None` without ignitions.
+    pub fn average_run_hours(&self) -> Option<f64> {
+        (self.ignitions > 0).then(|| f64::from(self.pellet_hours) / f64::from(self.ignitions))
+    }
+
+    /// Share of the burning time spent on logs, from 0 to 1.
+    pub fn log_share(&self) -> Option<f64> {
+        let log_hours = self.log_hours?;
+        let hours_burned = self.hours_burned();
+        Some(if hours_burned == 0 {
+            0.0
+        } else {
+            f64::from(log_hours) / f64::from(hours_burned)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use crate::consumption::WEEK;
+    use crate::runtime::{RuntimeSnapshot, RuntimeStats};
+
+    fn snapshot(days: u64, pellet_hours: i32, log_hours: i32, ignitions: i32) -> RuntimeSnapshot {
+        RuntimeSnapshot {
+            at: SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000 + days * 24 * 60 * 60),
+            pellet_hours,
+            log_hours,
+            ignitions,
+            on_off_cycles: 6,
+            counts_log_runtime: false,
+        }
+    }
+
+    #[test]
+    fn can_compute_runtime_stats() {
+        let stats = RuntimeStats::between(&snapshot(0, 297, 0, 121), &snapshot(7, 353, 0, 135));
+
+        assert_eq!(stats.period, WEEK);
+        assert_eq!(stats.hours_burned(), 56);
+        assert_eq!(stats.log_hours, None);
+        assert_eq!(stats.ignitions_per_day(), 2.0);
+        assert_eq!(stats.average_run_hours(), Some(4.0));
+        assert_eq!(stats.on_off_cycles, 0);
+        assert_eq!(stats.log_share(), None);
+    }
+
+    #[test]
+    fn can_split_log_and_pellet_runtime() {
+        let first = RuntimeSnapshot {
+            counts_log_runtime: true,
+            ..snapshot(0, 100, 40, 10)
+        };
+        let last = RuntimeSnapshot {
+            counts_log_runtime: true,
+            ..snapshot(2, 130, 50, 10)
+        };
+
+        let stats = RuntimeStats::between(&first, &last);
+
+        assert_eq!(stats.hours_burned(), 40);
+        assert_eq!(stats.log_hours, Some(10));
+        assert_eq!(stats.log_share(), Some(0.25));
+        assert_eq!(stats.average_run_hours(), None);
+    }
+}
@@ -0,0 +1,266 @@
+use std::collections::BTreeMap;
+
+use crate::model::{DailySchedule, HeatPeriod, HeatingSchedule, Weekday};
+
+/// How to fit more than two periods a day into the two slots the stove stores.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FitPolicy {
+    /// Join consecutive periods separated by the smallest gap, heating during the gap.
+    MergeSmallestGaps,
+    /// Remove the shortest periods, not heating during them anymore.
+    DropShortestPeriods,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FitAdjustment {
+    Merged {
+        first: HeatPeriod,
+        second: HeatPeriod,
+        gap_minutes: u16,
+    },
+    Dropped {
+        period: HeatPeriod,
+    },
+}
+
+impl FitAdjustment {
+    /// Heated minutes added (positive) or removed (negative) by this adjustment.
+    pub fn heated_minutes_delta(&self) -> i32 {
+        match self {
+            FitAdjustment::Merged { gap_minutes, .. } => i32::from(*gap_minutes),
+            FitAdjustment::Dropped { period } => -i32::from(period.duration_minutes()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FitReport {
+    pub adjustments: Vec<(Weekday, FitAdjustment)>,
+}
+
+impl FitReport {
+    pub fn added_minutes(&self) -> u32 {
+        self.adjustments
+            .iter()
+            .map(|(_, adjustment)| adjustment.heated_minutes_delta().max(0).unsigned_abs())
+            .sum()
+    }
+
+    pub fn removed_minutes(&self) -> u32 {
+        self.adjustments
+            .iter()
+            .map(|(_, adjustment)| adjustment.heated_minutes_delta().min(0).unsigned_abs())
+            .sum()
+    }
+
+    pub fn net_minutes(&self) -> i64 {
+        i64::from(self.added_minutes()) - i64::from(self.removed_minutes())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CompiledSchedule {
+    pub schedule: HeatingSchedule,
+    pub report: FitReport,
+}
+
+/// Builds a [`HeatingSchedule`] from any number of desired periods per day.
+///
+/// Overlapping or touching periods are joined first, this doesn't change the heated time and
+/// isn't reported. Zero length and reversed periods are ignored. Days missing from `desired`
+/// don't heat at all.
+pub fn compile_schedule(
+    desired: &BTreeMap<Weekday, Vec<HeatPeriod>>,
+    policy: FitPolicy,
+) -> CompiledSchedule {
+    let mut schedule = HeatingSchedule::all_same(DailySchedule::default());
+    let mut report = FitReport::default();
+    for (weekday, periods) in desired {
+        let (daily_schedule, adjustments) = fit_day(periods, policy);
+        *schedule.day_mut(*weekday) = daily_schedule;
+        report.adjustments.extend(
+            adjustments
+                .into_iter()
+                .map(|adjustment| (*weekday, adjustment)),
+        );
+    }
+    CompiledSchedule { schedule, report }
+}
+
+fn fit_day(periods: &[HeatPeriod], policy: FitPolicy) -> (DailySchedule, Vec<FitAdjustment>) {
+    let mut periods = join_overlapping(periods);
+    let mut adjustments = Vec::new();
+    while periods.len() > 2 {
+        match policy {
+            FitPolicy::MergeSmallestGaps => {
+                let index = (0..periods.len() - 1)
+                    .min_by_key(|&i| gap_minutes(&periods[i], &periods[i + 1]))
+                    .expect("at least 3 periods");
+                let second = periods.remove(index + 1);
+                let first = periods[index].clone();
+                periods[index] = span(&first, &second);
+                adjustments.push(FitAdjustment::Merged {
+                    gap_minutes: gap_minutes(&first, &second),
+                    first,
+                    second,
+                });
+            }
+            FitPolicy::DropShortestPeriods => {
+                let index = (0..periods.len())
+                    .min_by_key(|&i| periods[i].duration_minutes())
+                    .expect("at least 3 periods");
+                adjustments.push(FitAdjustment::Dropped {
+                    period: periods.remove(index),
+                });
+            }
+        }
+    }
+    let mut periods = periods.into_iter();
+    (
+        DailySchedule::new(periods.next().unwrap_or_default(), periods.next()),
+        adjustments,
+    )
+}
+
+fn join_overlapping(periods: &[HeatPeriod]) -> Vec<HeatPeriod> {
+    let mut sorted: Vec<HeatPeriod> = periods
+        .iter()
+        .filter(|period| period.begin() < period.end())
+        .cloned()
+        .collect();
+    sorted.sort();
+    let mut joined: Vec<HeatPeriod> = Vec::with_capacity(sorted.len());
+    for period in sorted {
+        match joined.last_mut() {
+            Some(last) if period.begin() <= last.end() => *last = span(last, &period),
+            _ => joined.push(period),
+        }
+    }
+    joined
+}
+
+fn gap_minutes(first: &HeatPeriod, second: &HeatPeriod) -> u16 {
+    second
+        .begin()
+        .minutes_of_day()
+        .saturating_sub(first.end().minutes_of_day())
+}
+
+fn span(first: &HeatPeriod, second: &HeatPeriod) -> HeatPeriod {
+    let end = first.end().max(second.end());
+    HeatPeriod::new(
+        first.begin().hours(),
+        first.begin().minutes(),
+        end.hours(),
+        end.minutes(),
+    )
+    .expect("a period beginning before it ends")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::model::{DailySchedule, HeatPeriod, Weekday};
+    use crate::schedule_compiler::{FitAdjustment, FitPolicy, compile_schedule};
+
+    fn period(begin_hours: u8, begin_minutes: u8, end_hours: u8, end_minutes: u8) -> HeatPeriod {
+        HeatPeriod::new(begin_hours, begin_minutes, end_hours, end_minutes).unwrap()
+    }
+
+    fn occupancy() -> BTreeMap<Weekday, Vec<HeatPeriod>> {
+        BTreeMap::from([
+            (
+                Weekday::Monday,
+                vec![
+                    period(18, 0, 22, 0),
+                    period(6, 30, 8, 0),
+                    period(12, 0, 13, 0),
+                    period(17, 30, 18, 0),
+                ],
+            ),
+            (
+                Weekday::Sunday,
+                vec![period(8, 0, 12, 0), period(11, 0, 14, 0)],
+            ),
+        ])
+    }
+
+    #[test]
+    fn can_fit_periods_by_merging_smallest_gaps() {
+        let compiled = compile_schedule(&occupancy(), FitPolicy::MergeSmallestGaps);
+
+        assert_eq!(
+            compiled.schedule.monday,
+            DailySchedule::dual(period(6, 30, 13, 0), period(17, 30, 22, 0))
+        );
+        assert_eq!(
+            compiled.schedule.sunday,
+            DailySchedule::single(period(8, 0, 14, 0))
+        );
+        assert_eq!(compiled.schedule.tuesday, DailySchedule::default());
+        assert_eq!(
+            compiled.report.adjustments,
+            vec![(
+                Weekday::Monday,
+                FitAdjustment::Merged {
+                    first: period(6, 30, 8, 0),
+                    second: period(12, 0, 13, 0),
+                    gap_minutes: 240,
+                }
+            )]
+        );
+        assert_eq!(compiled.report.added_minutes(), 240);
+        assert_eq!(compiled.report.removed_minutes(), 0);
+    }
+
+    #[test]
+    fn can_fit_periods_by_dropping_shortest_ones() {
+        let mut desired = occupancy();
+        desired.insert(
+            Weekday::Tuesday,
+            vec![
+                period(6, 0, 7, 0),
+                period(9, 0, 9, 15),
+                period(12, 0, 13, 0),
+                period(19, 0, 21, 0),
+            ],
+        );
+        let compiled = compile_schedule(&desired, FitPolicy::DropShortestPeriods);
+
+        assert_eq!(
+            compiled.schedule.monday,
+            DailySchedule::dual(period(6, 30, 8, 0), period(17, 30, 22, 0))
+        );
+        assert_eq!(
+            compiled.schedule.tuesday,
+            DailySchedule::dual(period(12, 0, 13, 0), period(19, 0, 21, 0))
+        );
+        assert_eq!(
+            compiled.report.adjustments,
+            vec![
+                (
+                    Weekday::Monday,
+                    FitAdjustment::Dropped {
+                        period: period(12, 0, 13, 0)
+                    }
+                ),
+                (
+                    Weekday::Tuesday,
+                    FitAdjustment::Dropped {
+                        period: period(9, 0, 9, 15)
+                    }
+                ),
+                (
+                    Weekday::Tuesday,
+                    FitAdjustment::Dropped {
+                        period: period(6, 0, 7, 0)
+                    }
+                ),
+            ]
+        );
+        assert_eq!(compiled.report.added_minutes(), 0);
+        assert_eq!(compiled.report.removed_minutes(), 60 + 15 + 60);
+        assert_eq!(compiled.report.net_minutes(), -135);
+    }
+}
@@ -1,6 +1,7 @@
 use reqwest::Client;
 use rika_firenet_client::{
-    HasDetailledStatus, RikaFirenet, RikaFirenetClient,
+    HasDetailledStatus, HasStatusReadings, RikaFirenet, RikaFirenetClient, Temperature,
+    error::StoveControlError,
     model::{DailySchedule, HeatPeriod, HeatingSchedule, StatusDetail},
 };
 use testcontainers::{
@@ -175,6 +176,55 @@ async fn can_turn_stove_off_and_on() {
     );
 }
 
+#[tokio::test]
+async fn can_bake() {
+    let container = start_rika_mock().await;
+    let client = RikaFirenetClient::builder()
+        .base_url(container.rika_firenet_base_url().await)
+        .build("registered-user@rika-firenet.com", "Secret");
+
+    let stove = client.status("333444".to_owned()).await.unwrap();
+    assert_eq!(
+        stove.get_oven_temperature(),
+        Some(Temperature::from_celsius(21.0)),
+        "oven temperature"
+    );
+    assert_eq!(stove.get_bake_temperature(), None, "no bake temperature");
+
+    client
+        .set_bake_mode("333444".to_owned(), 220)
+        .await
+        .unwrap();
+
+    let stove = client.status("333444".to_owned()).await.unwrap();
+    assert_eq!(stove.controls.operating_mode, Some(3), "bake mode");
+    assert_eq!(
+        stove.get_bake_temperature(),
+        Some(Temperature::from_celsius(220.0)),
+        "bake temperature"
+    );
+}
+
+#[tokio::test]
+async fn cant_bake_without_oven() {
+    let container = start_rika_mock().await;
+    let client = RikaFirenetClient::builder()
+        .base_url(container.rika_firenet_base_url().await)
+        .build("registered-user@rika-firenet.com", "Secret");
+
+    let error = client
+        .set_bake_mode("12345".to_owned(), 220)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<StoveControlError>(),
+        Some(&StoveControlError::unsupported("12345", "bake mode"))
+    );
+
+    let stove = client.status("12345".to_owned()).await.unwrap();
+    assert_eq!(stove.controls.operating_mode, Some(2), "still comfort mode");
+}
+
 #[tokio::test]
 async fn can_execute_sample_senario() {
     let container = start_rika_mock().await;
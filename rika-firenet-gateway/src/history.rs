@@ -0,0 +1,570 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use log::warn;
+use rika_firenet_client::{HasDetailledStatus, StoveStatus, model::StatusDetail};
+use rusqlite::{Connection, params};
+
+use crate::metrics::numeric_fields;
+use crate::poller::{FleetSnapshot, StovePoller};
+
+const DAY_SECONDS: u64 = 24 * 60 * 60;
+const PRUNE_EVERY: Duration = Duration::from_secs(60 * 60);
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS samples (
+        stove_id TEXT NOT NULL,
+        field TEXT NOT NULL,
+        at INTEGER NOT NULL,
+        value REAL NOT NULL,
+        PRIMARY KEY (stove_id, field, at)
+    );
+    CREATE TABLE IF NOT EXISTS details (
+        stove_id TEXT NOT NULL,
+        at INTEGER NOT NULL,
+        detail TEXT NOT NULL,
+        carried_over INTEGER NOT NULL DEFAULT 0,
+        PRIMARY KEY (stove_id, at)
+    );
+";
+
+/// Value of a field from `at` until the next sample.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sample {
+    pub at: SystemTime,
+    pub value: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DetailTransition {
+    pub at: SystemTime,
+    /// `None` for the first recorded detail
+    pub from: Option<StatusDetail>,
+    pub to: StatusDetail,
+}
+
+/// Statistics of a field over one UTC day, the average being weighted by duration.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DailyRollup {
+    /// Midnight UTC
+    pub day: SystemTime,
+    pub min: f64,
+    pub max: f64,
+    pub average: f64,
+}
+
+/// SQLite history of the stoves controls and sensors.
+///
+/// Fields are named after their group and snake cased Firenet name, e.g.
+/// `sensors_input_room_temperature` or `controls_target_temperature`. Only values which changed
+/// since the previous poll are stored, a value holds until the next sample of the field.
+pub struct HistoryStore {
+    connection: Connection,
+    last_values: HashMap<(String, String), f64>,
+    last_details: HashMap<String, StatusDetail>,
+}
+
+impl HistoryStore {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let connection = Connection::open(path)
+            .with_context(|| format!("Can't open history from {}", path.display()))?;
+        HistoryStore::with_connection(connection)
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        HistoryStore::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(connection: Connection) -> Result<Self> {
+        connection.execute_batch(SCHEMA)?;
+        let last_values = connection
+            .prepare(
+                "SELECT stove_id, field, value, MAX(at) FROM samples GROUP BY stove_id, field",
+            )?
+            .query_map([], |row| Ok(((row.get(0)?, row.get(1)?), row.get(2)?)))?
+            .collect::<Result<_, _>>()?;
+        let last_details = connection
+            .prepare("SELECT stove_id, detail, MAX(at) FROM details GROUP BY stove_id")?
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?
+            .filter_map(|row| match row {
+                Ok((stove_id, detail)) => {
+                    parse_detail(&detail).map(|detail| Ok((stove_id, detail)))
+                }
+                Err(error) => Some(Err(error)),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(HistoryStore {
+            connection,
+            last_values,
+            last_details,
+        })
+    }
+
+    /// Stores the fields and status detail which changed since the previous record of the stove.
+    ///
+    /// Returns the number of stored values.
+    pub fn record(&mut self, at: SystemTime, status: &StoveStatus) -> Result<usize> {
+        let at = seconds(at);
+        let stove_id = &status.stove_id;
+        let transaction = self.connection.transaction()?;
+        let mut stored = 0;
+        {
+            let mut insert_sample = transaction.prepare_cached(
+                "INSERT OR REPLACE INTO samples (stove_id, field, at, value) VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for (group, field, value) in numeric_fields(status) {
+                let key = (stove_id.clone(), format!("{group}_{field}"));
+                if self.last_values.get(&key) != Some(&value) {
+                    insert_sample.execute(params![key.0, key.1, at, value])?;
+                    self.last_values.insert(key, value);
+                    stored += 1;
+                }
+            }
+            let detail = status.get_status_details();
+            if self.last_details.get(stove_id) != Some(&detail) {
+                transaction.execute(
+                    "INSERT OR REPLACE INTO details (stove_id, at, detail) VALUES (?1, ?2, ?3)",
+                    params![stove_id, at, format!("{detail:?}")],
+                )?;
+                self.last_details.insert(stove_id.clone(), detail);
+                stored += 1;
+            }
+        }
+        transaction.commit()?;
+        Ok(stored)
+    }
+
+    /// Records every stove of the snapshot, except the ones which failed to poll.
+    pub fn record_snapshot(&mut self, snapshot: &FleetSnapshot) -> Result<()> {
+        let Some(polled_at) = snapshot.polled_at else {
+            return Ok(());
+        };
+        for (stove_id, status) in &snapshot.stoves {
+            if !snapshot.failures.contains_key(stove_id) {
+                self.record(polled_at, status)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Values of a field between `from` and `to`, starting with the value in effect at `from`.
+    pub fn series(
+        &self,
+        stove_id: &str,
+        field: &str,
+        from: SystemTime,
+        to: SystemTime,
+    ) -> Result<Vec<Sample>> {
+        let (from, to) = (seconds(from), seconds(to));
+        let mut samples = Vec::new();
+        let previous = self
+            .connection
+            .prepare_cached(
+                "SELECT value FROM samples WHERE stove_id = ?1 AND field = ?2 AND at < ?3
+                 ORDER BY at DESC LIMIT 1",
+            )?
+            .query_map(params![stove_id, field, from], |row| row.get(0))?
+            .next()
+            .transpose()?;
+        let mut statement = self.connection.prepare_cached(
+            "SELECT at, value FROM samples WHERE stove_id = ?1 AND field = ?2 AND at >= ?3 AND at < ?4
+             ORDER BY at",
+        )?;
+        for sample in statement.query_map(params![stove_id, field, from, to], |row| {
+            Ok(Sample {
+                at: time(row.get(0)?),
+                value: row.get(1)?,
+            })
+        })? {
+            samples.push(sample?);
+        }
+        if let Some(value) = previous
+            && samples.first().is_none_or(|first| seconds(first.at) > from)
+        {
+            samples.insert(
+                0,
+                Sample {
+                    at: time(from),
+                    value,
+                },
+            );
+        }
+        Ok(samples)
+    }
+
+    /// Status detail changes between `from` and `to`.
+    ///
+    /// Details carried over to the pruning boundary are no changes, they aren't reported.
+    pub fn transitions(
+        &self,
+        stove_id: &str,
+        from: SystemTime,
+        to: SystemTime,
+    ) -> Result<Vec<DetailTransition>> {
+        let mut statement = self.connection.prepare_cached(
+            "SELECT at, previous, detail FROM (
+                 SELECT at, detail, carried_over, LAG(detail) OVER (ORDER BY at) AS previous
+                 FROM details WHERE stove_id = ?1
+             ) WHERE at >= ?2 AND at < ?3 AND NOT carried_over ORDER BY at",
+        )?;
+        let rows = statement.query_map(params![stove_id, seconds(from), seconds(to)], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?;
+        let mut transitions = Vec::new();
+        for row in rows {
+            let (at, from, to) = row?;
+            if let Some(to) = parse_detail(&to) {
+                transitions.push(DetailTransition {
+                    at: time(at),
+                    from: from.as_deref().and_then(parse_detail),
+                    to,
+                });
+            }
+        }
+        Ok(transitions)
+    }
+
+    /// Rollups of the UTC days between `from` and `to`, days without any known value are skipped.
+    ///
+    /// The last value of a day holds until the end of the day, or until `to`.
+    pub fn daily_rollups(
+        &self,
+        stove_id: &str,
+        field: &str,
+        from: SystemTime,
+        to: SystemTime,
+    ) -> Result<Vec<DailyRollup>> {
+        let (from, to) = (seconds(from), seconds(to));
+        let mut rollups = Vec::new();
+        let mut day = from - from.rem_euclid(DAY_SECONDS as i64);
+        while day < to {
+            let (begin, end) = (day.max(from), (day + DAY_SECONDS as i64).min(to));
+            let samples = self.series(stove_id, field, time(begin), time(end))?;
+            if let Some(first) = samples.first() {
+                let (mut min, mut max, mut weighted_sum) = (first.value, first.value, 0.0);
+                for (i, sample) in samples.iter().enumerate() {
+                    let until = samples.get(i + 1).map_or(end, |next| seconds(next.at));
+                    weighted_sum += sample.value * (until - seconds(sample.at)) as f64;
+                    min = min.min(sample.value);
+                    max = max.max(sample.value);
+                }
+                let duration = (end - seconds(first.at)) as f64;
+                rollups.push(DailyRollup {
+                    day: time(day),
+                    min,
+                    max,
+                    average: if duration > 0.0 {
+                        weighted_sum / duration
+                    } else {
+                        first.value
+                    },
+                });
+            }
+            day += DAY_SECONDS as i64;
+        }
+        Ok(rollups)
+    }
+
+    /// Forgets history older than `before`, the values in effect at `before` are kept.
+    ///
+    /// Kept values are moved to `before`, kept details are also flagged as carried over so that
+    /// [`HistoryStore::transitions`] doesn't report them as changes.
+    pub fn prune(&mut self, before: SystemTime) -> Result<()> {
+        let before = seconds(before);
+        let transaction = self.connection.transaction()?;
+        transaction.execute(
+            "DELETE FROM samples AS old WHERE at < ?1 AND EXISTS (
+                 SELECT 1 FROM samples AS newer WHERE newer.stove_id = old.stove_id
+                 AND newer.field = old.field AND newer.at > old.at AND newer.at <= ?1
+             )",
+            params![before],
+        )?;
+        transaction.execute("UPDATE samples SET at = ?1 WHERE at < ?1", params![before])?;
+        transaction.execute(
+            "DELETE FROM details AS old WHERE at < ?1 AND EXISTS (
+                 SELECT 1 FROM details AS newer WHERE newer.stove_id = old.stove_id
+                 AND newer.at > old.at AND newer.at <= ?1
+             )",
+            params![before],
+        )?;
+        transaction.execute(
+            "UPDATE details SET at = ?1, carried_over = 1 WHERE at < ?1",
+            params![before],
+        )?;
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// Records each new snapshot, pruning history older than `retention` every hour.
+    ///
+    /// SQLite calls block, they run on the blocking thread pool.
+    pub async fn run(self, poller: Arc<StovePoller>, retention: Duration) {
+        let mut snapshots = poller.subscribe();
+        let mut last_pruned: Option<Instant> = None;
+        let mut store = self;
+        while snapshots.changed().await.is_ok() {
+            let snapshot = snapshots.borrow_and_update().clone();
+            let prune_before = last_pruned
+                .is_none_or(|pruned| pruned.elapsed() >= PRUNE_EVERY)
+                .then(|| {
+                    last_pruned = Some(Instant::now());
+                    SystemTime::now()
+                        .checked_sub(retention)
+                        .unwrap_or(UNIX_EPOCH)
+                });
+            let task = tokio::task::spawn_blocking(move || {
+                if let Err(error) = store.record_snapshot(&snapshot) {
+                    warn!("Unable to record stoves history: {error:#}");
+                }
+                if let Some(before) = prune_before
+                    && let Err(error) = store.prune(before)
+                {
+                    warn!("Unable to prune stoves history: {error:#}");
+                }
+                store
+            });
+            store = match task.await {
+                Ok(store) => store,
+                Err(error) => {
+                    warn!("Stoves history stopped: {error}");
+                    return;
+                }
+            };
+        }
+    }
+}
+
+fn seconds(at: SystemTime) -> i64 {
+    at.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as i64
+}
+
+fn time(seconds: i64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(seconds.max(0) as u64)
+}
+
+fn parse_detail(text: &str) -> Option<StatusDetail> {
+    StatusDetail::ALL
+        .into_iter()
+        .find(|detail| format!("{detail:?}") == text)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use rika_firenet_client::{StoveStatus, model::StatusDetail};
+
+    use crate::history::{DetailTransition, HistoryStore, Sample};
+    use crate::poller::tests::mock_status;
+
+    const ROOM_TEMPERATURE: &str = "sensors_input_room_temperature";
+
+    /// Hours since midnight UTC of an arbitrary day.
+    fn at(hours: f64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(20_000 * 24 * 3600 + (hours * 3600.0) as u64)
+    }
+
+    fn stove_status(room_temperature: &str, sub_state: u8) -> StoveStatus {
+        let mut status = mock_status("12345");
+        status.sensors.input_room_temperature = room_temperature.to_string();
+        status.sensors.status_sub_state = sub_state;
+        status
+    }
+
+    #[test]
+    fn only_stores_changed_values() {
+        let mut history = HistoryStore::open_in_memory().unwrap();
+
+        let stored = history.record(at(0.0), &stove_status("19.6", 3)).unwrap();
+        assert!(stored > 10);
+        assert_eq!(
+            history.record(at(1.0), &stove_status("19.6", 3)).unwrap(),
+            0
+        );
+        assert_eq!(
+            history.record(at(2.0), &stove_status("20.5", 3)).unwrap(),
+            1
+        );
+        assert_eq!(
+            history.record(at(3.0), &stove_status("20.5", 0)).unwrap(),
+            2
+        );
+    }
+
+    #[test]
+    fn can_query_a_field_between_two_times() {
+        let mut history = HistoryStore::open_in_memory().unwrap();
+        for (hours, temperature) in [(0.0, "18"), (2.0, "19.5"), (4.0, "21"), (6.0, "20")] {
+            history
+                .record(at(hours), &stove_status(temperature, 3))
+                .unwrap();
+        }
+
+        let series = history
+            .series("12345", ROOM_TEMPERATURE, at(3.0), at(6.0))
+            .unwrap();
+
+        assert_eq!(
+            series,
+            vec![
+                Sample {
+                    at: at(3.0),
+                    value: 19.5
+                },
+                Sample {
+                    at: at(4.0),
+                    value: 21.0
+                },
+            ]
+        );
+        assert_eq!(
+            history
+                .series("12345", ROOM_TEMPERATURE, at(4.0), at(5.0))
+                .unwrap(),
+            vec![Sample {
+                at: at(4.0),
+                value: 21.0
+            }]
+        );
+        assert!(
+            history
+                .series("333444", ROOM_TEMPERATURE, at(0.0), at(6.0))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn can_list_status_detail_transitions() {
+        let mut history = HistoryStore::open_in_memory().unwrap();
+        for (hours, sub_state) in [(0.0, 3), (10.0, 0), (30.0, 3), (40.0, 0)] {
+            history
+                .record(at(hours), &stove_status("19", sub_state))
+                .unwrap();
+        }
+
+        let yesterday = history.transitions("12345", at(24.0), at(48.0)).unwrap();
+
+        assert_eq!(
+            yesterday,
+            vec![
+                DetailTransition {
+                    at: at(30.0),
+                    from: Some(StatusDetail::Off),
+                    to: StatusDetail::Standby,
+                },
+                DetailTransition {
+                    at: at(40.0),
+                    from: Some(StatusDetail::Standby),
+                    to: StatusDetail::Off,
+                },
+            ]
+        );
+        let first = history.transitions("12345", at(0.0), at(1.0)).unwrap();
+        assert_eq!(first[0].from, None);
+    }
+
+    #[test]
+    fn can_roll_up_days() {
+        let mut history = HistoryStore::open_in_memory().unwrap();
+        for (hours, temperature) in [(6.0, "18"), (18.0, "22"), (30.0, "20")] {
+            history
+                .record(at(hours), &stove_status(temperature, 3))
+                .unwrap();
+        }
+
+        let rollups = history
+            .daily_rollups("12345", ROOM_TEMPERATURE, at(0.0), at(36.0))
+            .unwrap();
+
+        assert_eq!(rollups.len(), 2);
+        assert_eq!(rollups[0].day, at(0.0));
+        assert_eq!((rollups[0].min, rollups[0].max), (18.0, 22.0));
+        // 12h at 18 then 6h at 22
+        assert!((rollups[0].average - (12.0 * 18.0 + 6.0 * 22.0) / 18.0).abs() < 1e-9);
+        assert_eq!(rollups[1].day, at(24.0));
+        assert_eq!((rollups[1].min, rollups[1].max), (20.0, 22.0));
+        assert!((rollups[1].average - 21.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn prunes_history_but_keeps_current_values() {
+        let mut history = HistoryStore::open_in_memory().unwrap();
+        for (hours, temperature, sub_state) in [(0.0, "18", 3), (5.0, "19", 0), (30.0, "20", 0)] {
+            history
+                .record(at(hours), &stove_status(temperature, sub_state))
+                .unwrap();
+        }
+
+        history.prune(at(24.0)).unwrap();
+
+        let series = history
+            .series("12345", ROOM_TEMPERATURE, at(0.0), at(48.0))
+            .unwrap();
+        assert_eq!(
+            series,
+            vec![
+                Sample {
+                    at: at(24.0),
+                    value: 19.0
+                },
+                Sample {
+                    at: at(30.0),
+                    value: 20.0
+                },
+            ]
+        );
+        assert!(
+            history
+                .transitions("12345", at(0.0), at(48.0))
+                .unwrap()
+                .is_empty(),
+            "the detail carried over to the boundary isn't a change"
+        );
+        history.record(at(36.0), &stove_status("20", 3)).unwrap();
+        assert_eq!(
+            history.transitions("12345", at(0.0), at(48.0)).unwrap(),
+            vec![DetailTransition {
+                at: at(36.0),
+                from: Some(StatusDetail::Off),
+                to: StatusDetail::Standby,
+            }]
+        );
+        assert!(
+            history
+                .series("12345", "controls_target_temperature", at(24.0), at(25.0))
+                .unwrap()
+                .first()
+                .is_some_and(|sample| sample.value == 20.0)
+        );
+    }
+
+    #[test]
+    fn can_reopen_history() {
+        let path = std::env::temp_dir().join(format!("history-{}.sqlite", std::process::id()));
+        let mut history = HistoryStore::open(&path).unwrap();
+        history.record(at(0.0), &stove_status("19", 3)).unwrap();
+        drop(history);
+
+        let mut history = HistoryStore::open(&path).unwrap();
+        let stored = history.record(at(1.0), &stove_status("19", 3)).unwrap();
+        let series = history
+            .series("12345", ROOM_TEMPERATURE, at(0.0), at(2.0))
+            .unwrap();
+        drop(history);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(stored, 0);
+        assert_eq!(series.len(), 1);
+    }
+}
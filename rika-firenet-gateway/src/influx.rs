@@ -0,0 +1,382 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result, anyhow};
+use log::warn;
+use rika_firenet_client::{HasDetailledStatus, StoveStatus};
+
+use crate::metrics::numeric_fields;
+use crate::poller::{FleetSnapshot, StovePoller};
+
+/// Lines kept while the output is unavailable, in number of batches, the oldest are dropped.
+const MAX_PENDING_BATCHES: usize = 10;
+
+/// Where the line protocol is written.
+pub enum InfluxOutput {
+    /// Write endpoint including its query, e.g.
+    /// `http://localhost:8086/api/v2/write?org=home&bucket=stoves` or
+    /// `http://localhost:8086/write?db=stoves`
+    Http { url: String, token: Option<String> },
+    /// Lines are appended to the file
+    File(PathBuf),
+}
+
+/// Writes poller snapshots as InfluxDB line protocol, in batches.
+pub struct InfluxWriter {
+    output: InfluxOutput,
+    http_client: reqwest::Client,
+    batch_size: usize,
+    retries: u32,
+    retry_delay: Duration,
+    pending: Vec<String>,
+}
+
+impl InfluxWriter {
+    pub fn new(output: InfluxOutput) -> Self {
+        InfluxWriter {
+            output,
+            http_client: reqwest::Client::new(),
+            batch_size: 500,
+            retries: 3,
+            retry_delay: Duration::from_secs(1),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Number of lines sent at once, 500 by default.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Retries of a failed HTTP write, the delay doubling after each attempt.
+    pub fn with_retries(mut self, retries: u32, retry_delay: Duration) -> Self {
+        self.retries = retries;
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    /// Lines not written yet.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Queues the lines of a snapshot, writes them once a batch is complete.
+    pub async fn write(&mut self, snapshot: &FleetSnapshot) -> Result<()> {
+        self.pending.extend(lines(snapshot));
+        let max_pending = self.batch_size * MAX_PENDING_BATCHES;
+        if self.pending.len() > max_pending {
+            let dropped = self.pending.len() - max_pending;
+            warn!("Dropping {dropped} InfluxDB lines, the output is unavailable");
+            self.pending.drain(..dropped);
+        }
+        if self.pending.len() >= self.batch_size {
+            self.flush().await
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Writes every pending line, a failing batch stays pending unless InfluxDB rejected it.
+    pub async fn flush(&mut self) -> Result<()> {
+        while !self.pending.is_empty() {
+            let size = self.pending.len().min(self.batch_size);
+            let body = self.pending[..size].join("\n");
+            let result = self.send(body).await;
+            if !matches!(result, Err(WriteError::Unavailable(_))) {
+                self.pending.drain(..size);
+            }
+            if let Err(WriteError::Rejected(error) | WriteError::Unavailable(error)) = result {
+                return Err(error);
+            }
+        }
+        Ok(())
+    }
+
+    async fn send(&self, body: String) -> Result<(), WriteError> {
+        let (url, token) = match &self.output {
+            InfluxOutput::File(path) => {
+                // file writes block, they run on the blocking thread pool
+                let path = path.clone();
+                return tokio::task::spawn_blocking(move || append(&path, &body))
+                    .await
+                    .map_err(anyhow::Error::from)
+                    .and_then(|result| result)
+                    .map_err(WriteError::Unavailable);
+            }
+            InfluxOutput::Http { url, token } => (url, token),
+        };
+        let mut delay = self.retry_delay;
+        let mut attempt = 0;
+        loop {
+            let mut request = self.http_client.post(url).body(body.clone());
+            if let Some(token) = token {
+                request = request.header("Authorization", format!("Token {token}"));
+            }
+            let error = match request.send().await {
+                Ok(response) if response.status().is_success() => return Ok(()),
+                Ok(response)
+                    if response.status().is_client_error() && response.status().as_u16() != 429 =>
+                {
+                    let status = response.status();
+                    let message = response.text().await.unwrap_or_default();
+                    return Err(WriteError::Rejected(anyhow!(
+                        "InfluxDB rejected the batch with {status}: {message}"
+                    )));
+                }
+                Ok(response) => anyhow!("InfluxDB responded {}", response.status()),
+                Err(error) => anyhow::Error::new(error).context("Unable to reach InfluxDB"),
+            };
+            if attempt >= self.retries {
+                return Err(WriteError::Unavailable(error));
+            }
+            warn!("Retrying InfluxDB write in {delay:?}: {error:#}");
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+            attempt += 1;
+        }
+    }
+
+    /// Writes each new snapshot, flushing partial batches at the given interval.
+    pub async fn run(mut self, poller: Arc<StovePoller>, flush_interval: Duration) {
+        let mut snapshots = poller.subscribe();
+        let mut ticks = tokio::time::interval(flush_interval);
+        ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            let result = tokio::select! {
+                changed = snapshots.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                    let snapshot = snapshots.borrow_and_update().clone();
+                    self.write(&snapshot).await
+                }
+                _ = ticks.tick() => self.flush().await,
+            };
+            if let Err(error) = result {
+                warn!("Unable to write to InfluxDB: {error:#}");
+            }
+        }
+        if let Err(error) = self.flush().await {
+            warn!("Unable to write to InfluxDB: {error:#}");
+        }
+    }
+}
+
+enum WriteError {
+    /// Sending the batch again would fail again
+    Rejected(anyhow::Error),
+    Unavailable(anyhow::Error),
+}
+
+fn append(path: &Path, body: &str) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Can't open {}", path.display()))?;
+    writeln!(file, "{body}").with_context(|| format!("Can't write to {}", path.display()))
+}
+
+/// Renders the stoves of a snapshot in line protocol, stoves which failed to poll are skipped.
+///
+/// Each stove gets a `rika_stove` line plus `rika_controls` and `rika_sensors` lines holding
+/// every numeric or boolean field, tagged with `stove_id`, `name` and `oem`.
+pub fn lines(snapshot: &FleetSnapshot) -> Vec<String> {
+    let Some(polled_at) = snapshot.polled_at else {
+        return Vec::new();
+    };
+    snapshot
+        .stoves
+        .iter()
+        .filter(|(stove_id, _)| !snapshot.failures.contains_key(*stove_id))
+        .flat_map(|(_, status)| status_lines(polled_at, status))
+        .collect()
+}
+
+fn status_lines(at: SystemTime, status: &StoveStatus) -> Vec<String> {
+    let tags = format!(
+        "stove_id={},name={},oem={}",
+        escape_tag(&status.stove_id),
+        escape_tag(&status.name),
+        escape_tag(&status.oem)
+    );
+    let timestamp = at.duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
+    let mut controls = Vec::new();
+    let mut sensors = Vec::new();
+    for (group, field, value) in numeric_fields(status) {
+        let fields = if group == "controls" {
+            &mut controls
+        } else {
+            &mut sensors
+        };
+        fields.push(format!("{}={value}", escape_tag(&field)));
+    }
+    let stove = [
+        format!("last_seen_minutes={}", status.last_seen_minutes),
+        format!("status_detail=\"{:?}\"", status.get_status_details()),
+    ];
+    [
+        ("rika_stove", stove.to_vec()),
+        ("rika_controls", controls),
+        ("rika_sensors", sensors),
+    ]
+    .into_iter()
+    .filter(|(_, fields)| !fields.is_empty())
+    .map(|(measurement, fields)| format!("{measurement},{tags} {} {timestamp}", fields.join(",")))
+    .collect()
+}
+
+/// Tag keys, tag values and field keys escape commas, equal signs and spaces.
+fn escape_tag(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(',', "\\,")
+        .replace('=', "\\=")
+        .replace(' ', "\\ ")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use httpmock::{Method::POST, MockServer};
+
+    use crate::influx::{InfluxOutput, InfluxWriter, lines};
+    use crate::poller::{FleetSnapshot, tests::mock_status};
+
+    fn snapshot(stove_ids: &[&str]) -> FleetSnapshot {
+        FleetSnapshot {
+            polled_at: Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000)),
+            stoves: stove_ids
+                .iter()
+                .map(|stove_id| (stove_id.to_string(), mock_status(stove_id)))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn http_writer(server: &MockServer) -> InfluxWriter {
+        InfluxWriter::new(InfluxOutput::Http {
+            url: server.url("/api/v2/write?org=home&bucket=stoves"),
+            token: Some("secret-token".to_string()),
+        })
+        .with_retries(2, Duration::from_millis(1))
+    }
+
+    #[test]
+    fn can_render_line_protocol() {
+        let mut snapshot = snapshot(&["12345", "333444"]);
+        snapshot
+            .failures
+            .insert("333444".to_string(), "500".to_string());
+
+        let lines = lines(&snapshot);
+
+        assert_eq!(lines.len(), 3);
+        let tags = r"stove_id=12345,name=Stove\ 12345,oem=RIKA";
+        assert_eq!(
+            lines[0],
+            format!(
+                "rika_stove,{tags} last_seen_minutes=0,status_detail=\"Standby\" 1700000000000000000"
+            )
+        );
+        assert!(lines[1].starts_with(&format!("rika_controls,{tags} ")));
+        assert!(lines[1].contains(",target_temperature=20,"));
+        assert!(lines[1].contains(",on_off=1,"));
+        assert!(lines[2].starts_with(&format!("rika_sensors,{tags} ")));
+        assert!(lines[2].contains("input_room_temperature=19.6,"));
+        assert!(lines[2].ends_with(" 1700000000000000000"));
+        assert!(!lines[1].contains("heating_time_mon1"));
+        assert!(super::lines(&FleetSnapshot::default()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn can_write_in_batches() {
+        let server = MockServer::start();
+        let write_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/api/v2/write")
+                .query_param("bucket", "stoves")
+                .header("Authorization", "Token secret-token");
+            then.status(204);
+        });
+        let mut writer = http_writer(&server).with_batch_size(4);
+
+        writer.write(&snapshot(&["12345"])).await.unwrap();
+        write_mock.assert_calls(0);
+        assert_eq!(writer.pending(), 3);
+
+        writer.write(&snapshot(&["12345"])).await.unwrap();
+        write_mock.assert_calls(2);
+        assert_eq!(writer.pending(), 0);
+    }
+
+    #[tokio::test]
+    async fn keeps_lines_while_influxdb_is_unavailable() {
+        let server = MockServer::start();
+        let mut unavailable_mock = server.mock(|when, then| {
+            when.method(POST).path("/api/v2/write");
+            then.status(503);
+        });
+        let mut writer = http_writer(&server);
+        writer.write(&snapshot(&["12345"])).await.unwrap();
+
+        assert!(writer.flush().await.is_err());
+        unavailable_mock.assert_calls(3);
+        assert_eq!(writer.pending(), 3);
+
+        unavailable_mock.delete();
+        let write_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/api/v2/write")
+                .body_includes("rika_sensors,stove_id=12345");
+            then.status(204);
+        });
+        writer.flush().await.unwrap();
+        write_mock.assert();
+        assert_eq!(writer.pending(), 0);
+    }
+
+    #[tokio::test]
+    async fn drops_lines_rejected_by_influxdb() {
+        let server = MockServer::start();
+        let rejecting_mock = server.mock(|when, then| {
+            when.method(POST).path("/api/v2/write");
+            then.status(400).body("unable to parse");
+        });
+        let mut writer = http_writer(&server);
+        writer.write(&snapshot(&["12345"])).await.unwrap();
+
+        let error = writer.flush().await.unwrap_err();
+
+        assert!(error.to_string().contains("unable to parse"));
+        rejecting_mock.assert_calls(1);
+        assert_eq!(writer.pending(), 0);
+    }
+
+    #[tokio::test]
+    async fn can_write_to_a_file() {
+        let path = std::env::temp_dir().join(format!("influx-{}.txt", std::process::id()));
+        let mut writer = InfluxWriter::new(InfluxOutput::File(path.clone()));
+
+        writer.write(&snapshot(&["12345"])).await.unwrap();
+        writer.flush().await.unwrap();
+        writer.write(&snapshot(&["333444"])).await.unwrap();
+        writer.flush().await.unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(content.lines().count(), 6);
+        assert!(
+            content
+                .lines()
+                .nth(3)
+                .unwrap()
+                .starts_with("rika_stove,stove_id=333444,")
+        );
+    }
+}
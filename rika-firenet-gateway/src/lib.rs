@@ -0,0 +1,11 @@
+//! Local gateway polling Rika Firenet once and exporting stoves data to other systems.
+//!
+//! A single [`poller::StovePoller`] owns the Firenet session, every exporter reads its snapshots.
+
+pub mod history;
+pub mod influx;
+pub mod metrics;
+pub mod mqtt;
+pub mod poller;
+pub mod push;
+pub mod rest;
@@ -0,0 +1,110 @@
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use log::{info, warn};
+use rika_firenet_client::RikaFirenetClient;
+use rika_firenet_gateway::{
+    history::HistoryStore,
+    influx::{InfluxOutput, InfluxWriter},
+    metrics,
+    mqtt::{MqttBridge, MqttTopics},
+    poller::StovePoller,
+    push, rest,
+};
+use rumqttc::MqttOptions;
+
+fn env_or(name: &str, default: &str) -> String {
+    env::var(name).unwrap_or_else(|_| default.to_string())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    env_logger::init();
+
+    let email = env::var("RIKA_FIRENET_EMAIL").context("RIKA_FIRENET_EMAIL is required")?;
+    let password =
+        env::var("RIKA_FIRENET_PASSWORD").context("RIKA_FIRENET_PASSWORD is required")?;
+    let listen_address = env_or("GATEWAY_LISTEN_ADDRESS", "127.0.0.1:9090");
+    let poll_interval: u64 = env_or("GATEWAY_POLL_INTERVAL_SECONDS", "60")
+        .parse()
+        .context("GATEWAY_POLL_INTERVAL_SECONDS must be a number of seconds")?;
+
+    let client = RikaFirenetClient::builder()
+        .maybe_base_url(env::var("RIKA_FIRENET_BASE_URL").ok())
+        .build(email, password);
+    let poller = Arc::new(StovePoller::new(
+        Arc::new(client),
+        Duration::from_secs(poll_interval),
+    ));
+    tokio::spawn({
+        let poller = poller.clone();
+        async move { poller.run().await }
+    });
+
+    if let Ok(broker_host) = env::var("MQTT_BROKER_HOST") {
+        let broker_port: u16 = env_or("MQTT_BROKER_PORT", "1883")
+            .parse()
+            .context("MQTT_BROKER_PORT must be a port number")?;
+        let mut options = MqttOptions::new(
+            env_or("MQTT_CLIENT_ID", "rika-firenet-gateway"),
+            broker_host,
+            broker_port,
+        );
+        if let (Ok(username), Ok(password)) = (env::var("MQTT_USERNAME"), env::var("MQTT_PASSWORD"))
+        {
+            options.set_credentials(username, password);
+        }
+        let bridge = MqttBridge::new(
+            poller.clone(),
+            MqttTopics {
+                prefix: env_or("MQTT_TOPIC_PREFIX", "rika_firenet"),
+                discovery_prefix: env_or("MQTT_DISCOVERY_PREFIX", "homeassistant"),
+            },
+        );
+        tokio::spawn(async move {
+            if let Err(error) = bridge.run(options).await {
+                warn!("MQTT bridge stopped: {error:#}");
+            }
+        });
+    }
+
+    let influx_output = match (env::var("INFLUXDB_WRITE_URL"), env::var("INFLUXDB_FILE")) {
+        (Ok(url), _) => Some(InfluxOutput::Http {
+            url,
+            token: env::var("INFLUXDB_TOKEN").ok(),
+        }),
+        (_, Ok(path)) => Some(InfluxOutput::File(path.into())),
+        _ => None,
+    };
+    if let Some(output) = influx_output {
+        let writer = InfluxWriter::new(output);
+        tokio::spawn(writer.run(poller.clone(), Duration::from_secs(poll_interval)));
+    }
+
+    if let Ok(path) = env::var("HISTORY_DATABASE") {
+        let retention_days: u64 = env_or("HISTORY_RETENTION_DAYS", "365")
+            .parse()
+            .context("HISTORY_RETENTION_DAYS must be a number of days")?;
+        let history = HistoryStore::open(path)?;
+        tokio::spawn(history.run(
+            poller.clone(),
+            Duration::from_secs(retention_days * 24 * 60 * 60),
+        ));
+    }
+
+    let listener = tokio::net::TcpListener::bind(&listen_address)
+        .await
+        .with_context(|| format!("Unable to listen on {listen_address}"))?;
+    info!("Serving metrics on http://{listen_address}/metrics");
+    info!("Serving the stoves API on http://{listen_address}/stoves, see /openapi.json");
+    info!(
+        "Pushing stoves events on http://{listen_address}/events and ws://{listen_address}/events/ws"
+    );
+    let router = rest::router(poller.clone())
+        .merge(push::router(poller.clone()))
+        .merge(metrics::router(poller));
+    axum::serve(listener, router).await?;
+    Ok(())
+}
@@ -0,0 +1,275 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+use axum::{Router, extract::State, http::header, response::IntoResponse, routing::get};
+use rika_firenet_client::{HasDetailledStatus, StoveStatus, Temperature, model::StatusDetail};
+use serde_json::Value;
+
+use crate::poller::{FleetSnapshot, StovePoller};
+
+/// Serves the latest poller snapshot on `/metrics`, scrapes never reach Firenet.
+pub fn router(poller: Arc<StovePoller>) -> Router {
+    Router::new()
+        .route("/metrics", get(metrics))
+        .with_state(poller)
+}
+
+async fn metrics(State(poller): State<Arc<StovePoller>>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        render(&poller.latest()),
+    )
+}
+
+/// Samples of a metric family, keyed by their labels.
+type Family = Vec<(String, f64)>;
+
+/// Renders a snapshot in the Prometheus text exposition format.
+///
+/// Every numeric or boolean control and sensor becomes a gauge named after the Firenet field,
+/// e.g. `rika_sensors_input_room_temperature`. Temperatures sent as strings are converted and
+/// skipped when unavailable.
+pub fn render(snapshot: &FleetSnapshot) -> String {
+    let mut families: BTreeMap<String, Family> = BTreeMap::new();
+    for (stove_id, status) in &snapshot.stoves {
+        let labels = format!(
+            "stove_id=\"{}\",name=\"{}\"",
+            escape(stove_id),
+            escape(&status.name)
+        );
+        add_status(&mut families, &labels, status);
+    }
+    // a stove failing after a success keeps its previous status, list it once
+    let stove_ids: BTreeSet<_> = snapshot
+        .stoves
+        .keys()
+        .chain(snapshot.failures.keys())
+        .collect();
+    for stove_id in stove_ids {
+        let failed = snapshot.failures.contains_key(stove_id);
+        families
+            .entry("rika_poll_failed".to_string())
+            .or_default()
+            .push((
+                format!("stove_id=\"{}\"", escape(stove_id)),
+                f64::from(u8::from(failed)),
+            ));
+    }
+
+    let mut text = String::new();
+    if let Some(polled_at) = snapshot.polled_at {
+        let seconds = polled_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        let _ = writeln!(text, "# TYPE rika_last_poll_timestamp_seconds gauge");
+        let _ = writeln!(text, "rika_last_poll_timestamp_seconds {seconds}");
+    }
+    for (name, samples) in families {
+        let _ = writeln!(text, "# TYPE {name} gauge");
+        for (labels, value) in samples {
+            let _ = writeln!(text, "{name}{{{labels}}} {value}");
+        }
+    }
+    text
+}
+
+fn add_status(families: &mut BTreeMap<String, Family>, labels: &str, status: &StoveStatus) {
+    let mut add = |name: String, value: f64| {
+        families
+            .entry(name)
+            .or_default()
+            .push((labels.to_string(), value));
+    };
+    add(
+        "rika_last_seen_minutes".to_string(),
+        f64::from(status.last_seen_minutes),
+    );
+    for (group, field, value) in numeric_fields(status) {
+        add(format!("rika_{group}_{field}"), value);
+    }
+
+    let detail = status.get_status_details();
+    for state in StatusDetail::ALL {
+        families
+            .entry("rika_status_detail".to_string())
+            .or_default()
+            .push((
+                format!("{labels},detail=\"{state:?}\""),
+                f64::from(u8::from(state == detail)),
+            ));
+    }
+}
+
+/// Numeric and boolean fields of the `controls` and `sensors` groups, named in snake case.
+///
+/// Temperatures sent as strings are converted and skipped when unavailable.
+pub(crate) fn numeric_fields(status: &StoveStatus) -> Vec<(&'static str, String, f64)> {
+    let mut numeric_fields = Vec::new();
+    for (group, values) in [
+        ("controls", serde_json::to_value(&status.controls)),
+        ("sensors", serde_json::to_value(&status.sensors)),
+    ] {
+        let Ok(Value::Object(fields)) = values else {
+            continue;
+        };
+        for (field, value) in fields {
+            if let Some(value) = gauge_value(&field, &value) {
+                numeric_fields.push((group, snake_case(&field), value));
+            }
+        }
+    }
+    numeric_fields
+}
+
+fn gauge_value(field: &str, value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::Bool(flag) => Some(f64::from(u8::from(*flag))),
+        Value::String(text) if field.ends_with("Temperature") || field.ends_with("Offset") => {
+            // parsed as f64 again so that 19.6 isn't exported as 19.600000381469727
+            Temperature::parse_firenet(text).and(text.trim().parse().ok())
+        }
+        _ => None,
+    }
+}
+
+/// `parameterVersionTFTBootLoader` becomes `parameter_version_tft_boot_loader`.
+fn snake_case(field: &str) -> String {
+    let chars: Vec<char> = field.chars().collect();
+    let mut snake = String::with_capacity(field.len() + 8);
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            let previous = chars[i - 1];
+            let next_is_lower = chars
+                .get(i + 1)
+                .is_some_and(|next| next.is_ascii_lowercase());
+            if previous.is_ascii_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_ascii_uppercase() && next_is_lower)
+            {
+                snake.push('_');
+            }
+        }
+        snake.push(c.to_ascii_lowercase());
+    }
+    snake
+}
+
+fn escape(label_value: &str) -> String {
+    label_value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use httpmock::{Method::GET, MockServer};
+
+    use crate::metrics::{render, router, snake_case};
+    use crate::poller::tests::{firenet_mock, poller};
+
+    #[test]
+    fn can_name_metrics_after_firenet_fields() {
+        assert_eq!(snake_case("inputRoomTemperature"), "input_room_temperature");
+        assert_eq!(
+            snake_case("parameterVersionTFTBootLoader"),
+            "parameter_version_tft_boot_loader"
+        );
+        assert_eq!(snake_case("RoomPowerRequest"), "room_power_request");
+        assert_eq!(
+            snake_case("parameterErrorCount12"),
+            "parameter_error_count12"
+        );
+        assert_eq!(snake_case("convectionFan1Area"), "convection_fan1_area");
+    }
+
+    #[tokio::test]
+    async fn can_render_stove_metrics() {
+        let server = firenet_mock();
+        let poller = poller(&server);
+        poller.poll_once().await.unwrap();
+
+        let text = render(&poller.latest());
+
+        let labels = "{stove_id=\"12345\",name=\"Stove 12345\"}";
+        assert!(text.contains(&format!("rika_sensors_input_room_temperature{labels} 19.6")));
+        assert!(text.contains(&format!("rika_sensors_input_flame_temperature{labels} 13")));
+        assert!(text.contains(&format!("rika_controls_target_temperature{labels} 20")));
+        assert!(text.contains(&format!("rika_controls_on_off{labels} 1")));
+        assert!(text.contains(&format!(
+            "rika_sensors_parameter_ignition_count{labels} 121"
+        )));
+        assert!(!text.contains("rika_controls_bake_temperature"));
+        assert!(!text.contains("heating_time_mon1"));
+        assert!(text.contains(
+            "rika_status_detail{stove_id=\"12345\",name=\"Stove 12345\",detail=\"Standby\"} 1"
+        ));
+        assert!(text.contains(
+            "rika_status_detail{stove_id=\"12345\",name=\"Stove 12345\",detail=\"Running\"} 0"
+        ));
+        assert!(text.contains("rika_poll_failed{stove_id=\"333444\"} 1"));
+        assert_eq!(text.matches("# TYPE rika_status_detail gauge").count(), 1);
+    }
+
+    #[tokio::test]
+    async fn can_report_a_stove_failing_after_a_success_once() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/web/summary");
+            then.status(200).body_from_file("../mock/src/summary.html");
+        });
+        let mut status_mock = server.mock(|when, then| {
+            when.method(GET).path("/api/client/12345/status");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body_from_file("../mock/src/stove-status.json");
+        });
+        let poller = poller(&server);
+        poller.poll_once().await.unwrap();
+        assert!(render(&poller.latest()).contains("rika_poll_failed{stove_id=\"12345\"} 0"));
+        status_mock.delete();
+        server.mock(|when, then| {
+            when.method(GET).path("/api/client/12345/status");
+            then.status(500);
+        });
+        poller.poll_once().await.unwrap();
+
+        let text = render(&poller.latest());
+
+        assert!(text.contains("rika_sensors_input_room_temperature{stove_id=\"12345\""));
+        assert_eq!(
+            text.matches("rika_poll_failed{stove_id=\"12345\"}").count(),
+            1
+        );
+        assert!(text.contains("rika_poll_failed{stove_id=\"12345\"} 1"));
+    }
+
+    #[tokio::test]
+    async fn can_serve_metrics() {
+        let server = firenet_mock();
+        let poller = Arc::new(poller(&server));
+        poller.poll_once().await.unwrap();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(axum::serve(listener, router(poller)).into_future());
+
+        let response = reqwest::get(format!("http://{address}/metrics"))
+            .await
+            .unwrap();
+
+        assert!(response.status().is_success());
+        assert!(
+            response
+                .text()
+                .await
+                .unwrap()
+                .contains("rika_sensors_input_room_temperature")
+        );
+    }
+}
@@ -0,0 +1,725 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail, ensure};
+use log::{info, warn};
+use rika_firenet_client::{
+    HasDetailledStatus, HasStatusReadings, RikaFirenet, StoveStatus, limits::LimitsTable,
+    model::OperatingMode,
+};
+use rumqttc::{AsyncClient, Event, MqttOptions, Packet, Publish, QoS};
+use serde_json::{Value, json};
+use tokio::sync::mpsc;
+
+use crate::poller::{FleetSnapshot, StovePoller};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MqttMessage {
+    pub topic: String,
+    pub payload: String,
+    pub retain: bool,
+}
+
+/// A command received on a `<prefix>/<stove id>/<name>/set` topic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StoveCommand {
+    /// `mode/set` with `heat` or `off`, as sent by Home Assistant climate entities
+    Power(bool),
+    /// `operating_mode/set` with `manual`, `auto`, `comfort` or `bake`, keeping current settings
+    OperatingMode(u8),
+    /// `heating_power/set`, applied to the current manual or auto mode
+    HeatingPower(u8),
+    /// `target_temperature/set`, switches to comfort mode
+    TargetTemperature(u8),
+    /// `idle_temperature/set`, switches to comfort mode
+    IdleTemperature(u8),
+    /// `frost_protection/set` with a temperature or `OFF`
+    FrostProtection(Option<u8>),
+}
+
+/// Topic names of the bridge.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MqttTopics {
+    /// Prefix of state and command topics
+    pub prefix: String,
+    /// Home Assistant discovery prefix
+    pub discovery_prefix: String,
+}
+
+impl Default for MqttTopics {
+    fn default() -> Self {
+        MqttTopics {
+            prefix: "rika_firenet".to_string(),
+            discovery_prefix: "homeassistant".to_string(),
+        }
+    }
+}
+
+/// Option of the operating mode select, `None` resets the Home Assistant state to unknown.
+fn operating_mode_name(mode: Option<u8>) -> &'static str {
+    match mode.map(i32::from).map(OperatingMode::parse) {
+        Some(Ok(OperatingMode::Manual)) => "manual",
+        Some(Ok(OperatingMode::Auto)) => "auto",
+        Some(Ok(OperatingMode::Comfort)) => "comfort",
+        Some(Ok(OperatingMode::Bake)) => "bake",
+        _ => "None",
+    }
+}
+
+fn parse_rounded(payload: &str) -> Result<u8> {
+    let value: f32 = payload
+        .trim()
+        .parse()
+        .with_context(|| format!("{payload} is not a number"))?;
+    let rounded = value.round();
+    ensure!(
+        (0.0..=255.0).contains(&rounded),
+        "{payload} is out of the 0 to 255 range"
+    );
+    Ok(rounded as u8)
+}
+
+impl MqttTopics {
+    fn stove_topic(&self, stove_id: &str, name: &str) -> String {
+        format!("{}/{stove_id}/{name}", self.prefix)
+    }
+
+    pub fn command_filter(&self) -> String {
+        format!("{}/+/+/set", self.prefix)
+    }
+
+    /// Retained state messages, `<prefix>/<stove id>/status` holds the whole status as JSON.
+    pub fn state_messages(&self, stove_id: &str, status: &StoveStatus) -> Vec<MqttMessage> {
+        let controls = &status.controls;
+        let temperature = |value: Option<rika_firenet_client::Temperature>| {
+            value.map(|t| t.to_string()).unwrap_or_default()
+        };
+        let frost_protection = match controls.frost_protection_active {
+            Some(true) => temperature(controls.frost_protection_temperature()),
+            _ => "OFF".to_string(),
+        };
+        [
+            ("status", serde_json::to_string(status).unwrap_or_default()),
+            ("detail", format!("{:?}", status.get_status_details())),
+            (
+                "mode",
+                if controls.on_off == Some(true) {
+                    "heat"
+                } else {
+                    "off"
+                }
+                .to_string(),
+            ),
+            (
+                "operating_mode",
+                operating_mode_name(controls.operating_mode).to_string(),
+            ),
+            (
+                "heating_power",
+                controls
+                    .heating_power
+                    .map(|power| power.to_string())
+                    .unwrap_or_default(),
+            ),
+            (
+                "room_temperature",
+                temperature(status.sensors.input_room_temperature()),
+            ),
+            (
+                "flame_temperature",
+                status.sensors.input_flame_temperature.to_string(),
+            ),
+            (
+                "target_temperature",
+                temperature(controls.target_temperature()),
+            ),
+            (
+                "idle_temperature",
+                temperature(controls.set_back_temperature()),
+            ),
+            ("frost_protection", frost_protection),
+            (
+                "pellets_total_kg",
+                status.sensors.parameter_feed_rate_total.to_string(),
+            ),
+        ]
+        .into_iter()
+        .map(|(name, payload)| MqttMessage {
+            topic: self.stove_topic(stove_id, name),
+            payload,
+            retain: true,
+        })
+        .collect()
+    }
+
+    /// Home Assistant discovery configs: a climate entity, sensors and mode controls.
+    ///
+    /// Value ranges come from the limits of the stove model.
+    pub fn discovery_messages(
+        &self,
+        stove_id: &str,
+        status: &StoveStatus,
+        limits: &LimitsTable,
+    ) -> Vec<MqttMessage> {
+        let limits = limits.get(&status.oem, &status.stove_type);
+        let object_id = format!("rika_{stove_id}");
+        let device = json!({
+            "identifiers": [object_id],
+            "name": status.name,
+            "manufacturer": status.oem,
+            "model": status.stove_type,
+        });
+        let topic = |name: &str| self.stove_topic(stove_id, name);
+        let config = |component: &str, key: Option<&str>, mut config: Value| {
+            let unique_id = match key {
+                Some(key) => format!("{object_id}_{key}"),
+                None => object_id.clone(),
+            };
+            config["unique_id"] = json!(unique_id);
+            config["device"] = device.clone();
+            MqttMessage {
+                topic: format!("{}/{component}/{unique_id}/config", self.discovery_prefix),
+                payload: config.to_string(),
+                retain: true,
+            }
+        };
+        let mut operating_modes = vec!["manual", "auto", "comfort"];
+        if status.stove_features.bake_mode {
+            operating_modes.push("bake");
+        }
+        let sensor = |key: &str, name: &str, extra: Value| {
+            let mut value = json!({ "name": name, "state_topic": topic(key) });
+            if let (Value::Object(value), Value::Object(extra)) = (&mut value, extra) {
+                value.extend(extra);
+            }
+            config("sensor", Some(key), value)
+        };
+
+        vec![
+            config(
+                "climate",
+                None,
+                json!({
+                    "name": null,
+                    "modes": ["off", "heat"],
+                    "mode_state_topic": topic("mode"),
+                    "mode_command_topic": topic("mode/set"),
+                    "current_temperature_topic": topic("room_temperature"),
+                    "temperature_state_topic": topic("target_temperature"),
+                    "temperature_command_topic": topic("target_temperature/set"),
+                    "min_temp": limits.target_temperature.min,
+                    "max_temp": limits.target_temperature.max,
+                    "temp_step": limits.target_temperature.step,
+                    "temperature_unit": "C",
+                }),
+            ),
+            config(
+                "select",
+                Some("operating_mode"),
+                json!({
+                    "name": "Operating mode",
+                    "options": operating_modes,
+                    "state_topic": topic("operating_mode"),
+                    "command_topic": topic("operating_mode/set"),
+                }),
+            ),
+            config(
+                "number",
+                Some("heating_power"),
+                json!({
+                    "name": "Heating power",
+                    "min": limits.heating_power.min,
+                    "max": limits.heating_power.max,
+                    "step": limits.heating_power.step,
+                    "unit_of_measurement": "%",
+                    "state_topic": topic("heating_power"),
+                    "command_topic": topic("heating_power/set"),
+                }),
+            ),
+            sensor(
+                "room_temperature",
+                "Room temperature",
+                json!({ "device_class": "temperature", "unit_of_measurement": "°C", "state_class": "measurement" }),
+            ),
+            sensor(
+                "flame_temperature",
+                "Flame temperature",
+                json!({ "device_class": "temperature", "unit_of_measurement": "°C", "state_class": "measurement" }),
+            ),
+            sensor("detail", "Status", json!({})),
+            sensor(
+                "pellets_total_kg",
+                "Pellets burnt",
+                json!({ "device_class": "weight", "unit_of_measurement": "kg", "state_class": "total_increasing" }),
+            ),
+        ]
+    }
+
+    /// `Ok(None)` when the topic isn't a command topic of this bridge.
+    pub fn parse_command(
+        &self,
+        topic: &str,
+        payload: &str,
+    ) -> Result<Option<(String, StoveCommand)>> {
+        let Some(path) = topic
+            .strip_prefix(&self.prefix)
+            .and_then(|path| path.strip_prefix('/'))
+            .and_then(|path| path.strip_suffix("/set"))
+        else {
+            return Ok(None);
+        };
+        let Some((stove_id, name)) = path.split_once('/') else {
+            return Ok(None);
+        };
+        let payload = payload.trim();
+        let command = match name {
+            "mode" => match payload.to_lowercase().as_str() {
+                "heat" | "on" => StoveCommand::Power(true),
+                "off" => StoveCommand::Power(false),
+                _ => bail!("Unsupported mode {payload}"),
+            },
+            "operating_mode" => StoveCommand::OperatingMode(
+                match payload.to_lowercase().as_str() {
+                    "manual" => OperatingMode::Manual,
+                    "auto" => OperatingMode::Auto,
+                    "comfort" => OperatingMode::Comfort,
+                    "bake" => OperatingMode::Bake,
+                    _ => bail!("Unsupported operating mode {payload}"),
+                }
+                .into(),
+            ),
+            "heating_power" => StoveCommand::HeatingPower(parse_rounded(payload)?),
+            "target_temperature" => StoveCommand::TargetTemperature(parse_rounded(payload)?),
+            "idle_temperature" => StoveCommand::IdleTemperature(parse_rounded(payload)?),
+            "frost_protection" => {
+                StoveCommand::FrostProtection(if payload.eq_ignore_ascii_case("off") {
+                    None
+                } else {
+                    Some(parse_rounded(payload)?)
+                })
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some((stove_id.to_string(), command)))
+    }
+}
+
+/// Invokes the [`RikaFirenet`] operation matching the command, missing settings are taken from
+/// the current status.
+pub async fn execute(
+    firenet: &(dyn RikaFirenet + Send + Sync),
+    stove_id: String,
+    command: StoveCommand,
+    status: &StoveStatus,
+) -> Result<()> {
+    let controls = &status.controls;
+    let heating_power = || controls.heating_power.context("Unknown heating power");
+    let celsius = |value: Option<rika_firenet_client::Temperature>, name: &str| {
+        value
+            .map(|t| t.celsius().round() as u8)
+            .with_context(|| format!("Unknown {name}"))
+    };
+    let idle = || celsius(controls.set_back_temperature(), "idle temperature");
+    let target = || celsius(controls.target_temperature(), "target temperature");
+    let bake = || {
+        status
+            .get_bake_temperature()
+            .map(|t| t.celsius().round() as u16)
+            .context("Unknown bake temperature")
+    };
+    let is_auto = matches!(
+        controls
+            .operating_mode
+            .map(i32::from)
+            .map(OperatingMode::parse),
+        Some(Ok(OperatingMode::Auto))
+    );
+    match command {
+        StoveCommand::Power(true) => firenet.turn_on(stove_id).await,
+        StoveCommand::Power(false) => firenet.turn_off(stove_id).await,
+        StoveCommand::OperatingMode(mode) => match OperatingMode::parse(i32::from(mode))? {
+            OperatingMode::Manual => firenet.set_manual_mode(stove_id, heating_power()?).await,
+            OperatingMode::Auto => firenet.set_auto_mode(stove_id, heating_power()?).await,
+            OperatingMode::Comfort => firenet.set_comfort_mode(stove_id, idle()?, target()?).await,
+            OperatingMode::Bake => firenet.set_bake_mode(stove_id, bake()?).await,
+        },
+        StoveCommand::HeatingPower(power) if is_auto => {
+            firenet.set_auto_mode(stove_id, power).await
+        }
+        StoveCommand::HeatingPower(power) => firenet.set_manual_mode(stove_id, power).await,
+        StoveCommand::TargetTemperature(target) => {
+            firenet.set_comfort_mode(stove_id, idle()?, target).await
+        }
+        StoveCommand::IdleTemperature(idle) => {
+            firenet.set_comfort_mode(stove_id, idle, target()?).await
+        }
+        StoveCommand::FrostProtection(Some(temperature)) => {
+            firenet.enable_frost_protection(stove_id, temperature).await
+        }
+        StoveCommand::FrostProtection(None) => firenet.disable_frost_protection(stove_id).await,
+    }
+}
+
+/// Publishes poller snapshots to MQTT and forwards commands to Firenet.
+pub struct MqttBridge {
+    poller: Arc<StovePoller>,
+    topics: MqttTopics,
+    limits: LimitsTable,
+}
+
+enum Incoming {
+    Connected,
+    Command(Publish),
+}
+
+impl MqttBridge {
+    pub fn new(poller: Arc<StovePoller>, topics: MqttTopics) -> Self {
+        MqttBridge {
+            poller,
+            topics,
+            limits: LimitsTable::default(),
+        }
+    }
+
+    /// Limits announced to Home Assistant by stove model, e.g. the climate entity temperature
+    /// range. Use the table given to the Firenet client so that both accept the same values.
+    pub fn with_limits(mut self, limits: LimitsTable) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Runs until the poller is dropped, reconnecting to the broker when needed.
+    pub async fn run(&self, options: MqttOptions) -> Result<()> {
+        let (client, mut event_loop) = AsyncClient::new(options, 256);
+        let (incoming_sender, mut incoming) = mpsc::channel(64);
+        tokio::spawn(async move {
+            loop {
+                let forwarded = match event_loop.poll().await {
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => Some(Incoming::Connected),
+                    Ok(Event::Incoming(Packet::Publish(publish))) => {
+                        Some(Incoming::Command(publish))
+                    }
+                    Ok(_) => None,
+                    Err(error) => {
+                        warn!("MQTT connection error: {error}");
+                        tokio::time::sleep(Duration::from_secs(1)).await;
+                        None
+                    }
+                };
+                if let Some(forwarded) = forwarded
+                    && incoming_sender.send(forwarded).await.is_err()
+                {
+                    return;
+                }
+            }
+        });
+
+        // commands run on their own task, so that Firenet calls don't delay publishing
+        let (command_sender, mut commands) = mpsc::channel::<Publish>(64);
+        let (poller, topics) = (self.poller.clone(), self.topics.clone());
+        tokio::spawn(async move {
+            while let Some(publish) = commands.recv().await {
+                handle(&poller, &topics, publish).await;
+            }
+        });
+
+        let mut snapshots = self.poller.subscribe();
+        let mut discovered = HashSet::new();
+        loop {
+            tokio::select! {
+                received = incoming.recv() => match received {
+                    Some(Incoming::Connected) => {
+                        info!("Connected to MQTT broker");
+                        if let Err(error) = client
+                            .subscribe(self.topics.command_filter(), QoS::AtLeastOnce)
+                            .await
+                        {
+                            warn!("Unable to subscribe to MQTT commands: {error}");
+                        }
+                        discovered.clear();
+                        let snapshot = snapshots.borrow_and_update().clone();
+                        self.publish(&client, &snapshot, &mut discovered).await;
+                    }
+                    Some(Incoming::Command(publish)) => {
+                        if command_sender.try_send(publish).is_err() {
+                            warn!("Dropping MQTT command, previous commands are still running");
+                        }
+                    }
+                    None => bail!("MQTT event loop stopped"),
+                },
+                changed = snapshots.changed() => {
+                    changed.context("Stove poller stopped")?;
+                    let snapshot = snapshots.borrow_and_update().clone();
+                    self.publish(&client, &snapshot, &mut discovered).await;
+                }
+            }
+        }
+    }
+
+    /// Publishes the snapshot, failures are logged and retried with the next snapshot or
+    /// connection.
+    async fn publish(
+        &self,
+        client: &AsyncClient,
+        snapshot: &FleetSnapshot,
+        discovered: &mut HashSet<String>,
+    ) {
+        for (stove_id, status) in &snapshot.stoves {
+            let mut messages = Vec::new();
+            if discovered.insert(stove_id.clone()) {
+                messages.extend(
+                    self.topics
+                        .discovery_messages(stove_id, status, &self.limits),
+                );
+            }
+            messages.extend(self.topics.state_messages(stove_id, status));
+            for message in messages {
+                if let Err(error) = client
+                    .publish(
+                        message.topic,
+                        QoS::AtLeastOnce,
+                        message.retain,
+                        message.payload,
+                    )
+                    .await
+                {
+                    warn!("Unable to publish stove {stove_id} to MQTT: {error}");
+                    discovered.remove(stove_id);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Applies a command then refreshes the stoves. The refresh waits for a background poll in
+/// progress, so the state published afterwards is never older than the command.
+async fn handle(poller: &StovePoller, topics: &MqttTopics, publish: Publish) {
+    let payload = String::from_utf8_lossy(&publish.payload);
+    let (stove_id, command) = match topics.parse_command(&publish.topic, &payload) {
+        Ok(Some(command)) => command,
+        Ok(None) => return,
+        Err(error) => {
+            warn!("Ignoring {} {payload}: {error:#}", publish.topic);
+            return;
+        }
+    };
+    let Some(status) = poller.latest().stoves.get(&stove_id).cloned() else {
+        warn!("Ignoring {command:?} for unknown stove {stove_id}");
+        return;
+    };
+    let firenet = poller.firenet().as_ref();
+    if let Err(error) = execute(firenet, stove_id.clone(), command, &status).await {
+        warn!("Unable to apply {command:?} to stove {stove_id}: {error:#}");
+        return;
+    }
+    if let Err(error) = poller.poll_once().await {
+        warn!("Unable to refresh stoves after a command: {error:#}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use httpmock::{Method::POST, MockServer};
+    use regex::Regex;
+    use rika_firenet_client::{
+        StoveStatus,
+        limits::{Limit, LimitsTable, StoveLimits},
+    };
+    use serde_json::Value;
+
+    use crate::mqtt::{MqttTopics, StoveCommand, execute};
+    use crate::poller::tests::{firenet_mock, mock_status, poller};
+
+    fn payload<'a>(messages: &'a [crate::mqtt::MqttMessage], topic: &str) -> &'a str {
+        &messages
+            .iter()
+            .find(|message| message.topic == topic)
+            .unwrap_or_else(|| panic!("a message on {topic}"))
+            .payload
+    }
+
+    #[test]
+    fn can_publish_stove_states() {
+        let messages = MqttTopics::default().state_messages("12345", &mock_status("12345"));
+
+        assert!(messages.iter().all(|message| message.retain));
+        assert_eq!(payload(&messages, "rika_firenet/12345/detail"), "Standby");
+        assert_eq!(payload(&messages, "rika_firenet/12345/mode"), "heat");
+        assert_eq!(
+            payload(&messages, "rika_firenet/12345/operating_mode"),
+            "comfort"
+        );
+        assert_eq!(
+            payload(&messages, "rika_firenet/12345/room_temperature"),
+            "19.6"
+        );
+        assert_eq!(
+            payload(&messages, "rika_firenet/12345/target_temperature"),
+            "20"
+        );
+        assert_eq!(
+            payload(&messages, "rika_firenet/12345/frost_protection"),
+            "OFF"
+        );
+        let status: StoveStatus =
+            serde_json::from_str(payload(&messages, "rika_firenet/12345/status")).unwrap();
+        assert_eq!(status, mock_status("12345"));
+    }
+
+    #[test]
+    fn can_announce_the_limits_of_the_stove_model() {
+        let limits = LimitsTable::default().with_model(
+            "RIKA",
+            "DOMO",
+            StoveLimits {
+                target_temperature: Limit::new(10.0, 30.0, 1.0),
+                heating_power: Limit::new(30.0, 100.0, 5.0),
+                ..Default::default()
+            },
+        );
+        let messages =
+            MqttTopics::default().discovery_messages("12345", &mock_status("12345"), &limits);
+
+        let climate: Value = serde_json::from_str(payload(
+            &messages,
+            "homeassistant/climate/rika_12345/config",
+        ))
+        .unwrap();
+        assert_eq!(climate["min_temp"], 10.0);
+        assert_eq!(climate["max_temp"], 30.0);
+        let heating_power: Value = serde_json::from_str(payload(
+            &messages,
+            "homeassistant/number/rika_12345_heating_power/config",
+        ))
+        .unwrap();
+        assert_eq!(heating_power["min"], 30.0);
+        assert_eq!(heating_power["step"], 5.0);
+    }
+
+    #[test]
+    fn can_publish_home_assistant_discovery() {
+        let messages = MqttTopics::default().discovery_messages(
+            "12345",
+            &mock_status("12345"),
+            &LimitsTable::default(),
+        );
+
+        let climate: Value = serde_json::from_str(payload(
+            &messages,
+            "homeassistant/climate/rika_12345/config",
+        ))
+        .unwrap();
+        assert_eq!(climate["unique_id"], "rika_12345");
+        assert_eq!(climate["device"]["model"], "DOMO");
+        assert_eq!(
+            climate["temperature_command_topic"],
+            "rika_firenet/12345/target_temperature/set"
+        );
+        assert_eq!(climate["max_temp"], 28.0);
+        let room: Value = serde_json::from_str(payload(
+            &messages,
+            "homeassistant/sensor/rika_12345_room_temperature/config",
+        ))
+        .unwrap();
+        assert_eq!(room["state_topic"], "rika_firenet/12345/room_temperature");
+        assert_eq!(room["device_class"], "temperature");
+        let operating_mode = |status: &StoveStatus| -> Value {
+            let messages =
+                MqttTopics::default().discovery_messages("12345", status, &LimitsTable::default());
+            serde_json::from_str(payload(
+                &messages,
+                "homeassistant/select/rika_12345_operating_mode/config",
+            ))
+            .unwrap()
+        };
+        assert_eq!(
+            operating_mode(&mock_status("12345"))["options"],
+            serde_json::json!(["manual", "auto", "comfort"])
+        );
+        let mut oven = mock_status("12345");
+        oven.stove_features.bake_mode = true;
+        assert_eq!(operating_mode(&oven)["options"][3], "bake");
+    }
+
+    #[test]
+    fn can_reset_unknown_operating_modes() {
+        let mut status = mock_status("12345");
+        status.controls.operating_mode = Some(9);
+
+        let messages = MqttTopics::default().state_messages("12345", &status);
+
+        assert_eq!(
+            payload(&messages, "rika_firenet/12345/operating_mode"),
+            "None"
+        );
+    }
+
+    #[test]
+    fn can_parse_commands() {
+        let topics = MqttTopics::default();
+        let parse = |topic: &str, payload: &str| topics.parse_command(topic, payload).unwrap();
+
+        assert_eq!(
+            parse("rika_firenet/12345/mode/set", "off"),
+            Some(("12345".to_string(), StoveCommand::Power(false)))
+        );
+        assert_eq!(
+            parse("rika_firenet/12345/operating_mode/set", "auto"),
+            Some(("12345".to_string(), StoveCommand::OperatingMode(1)))
+        );
+        assert_eq!(
+            parse("rika_firenet/12345/target_temperature/set", "21.0"),
+            Some(("12345".to_string(), StoveCommand::TargetTemperature(21)))
+        );
+        assert_eq!(
+            parse("rika_firenet/12345/frost_protection/set", "OFF"),
+            Some(("12345".to_string(), StoveCommand::FrostProtection(None)))
+        );
+        assert_eq!(
+            parse("rika_firenet/12345/operating_mode/set", "bake"),
+            Some(("12345".to_string(), StoveCommand::OperatingMode(3)))
+        );
+        assert_eq!(parse("rika_firenet/12345/status", "{}"), None);
+        assert_eq!(parse("other/12345/mode/set", "off"), None);
+        assert!(
+            topics
+                .parse_command("rika_firenet/12345/mode/set", "cool")
+                .is_err()
+        );
+        for out_of_range in ["-5", "1e9", "NaN"] {
+            assert!(
+                topics
+                    .parse_command("rika_firenet/12345/heating_power/set", out_of_range)
+                    .is_err(),
+                "{out_of_range}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn can_execute_commands_keeping_current_settings() {
+        let server: MockServer = firenet_mock();
+        let control_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/api/client/12345/controls")
+                .body_matches(Regex::new("(^|&)operatingMode=2(&|$)").unwrap())
+                .body_matches(Regex::new("(^|&)setBackTemperature=18(&|$)").unwrap())
+                .body_matches(Regex::new("(^|&)targetTemperature=22(&|$)").unwrap());
+            then.status(200).body("OK");
+        });
+        let poller = poller(&server);
+
+        execute(
+            poller.firenet().as_ref(),
+            "12345".to_string(),
+            StoveCommand::TargetTemperature(22),
+            &mock_status("12345"),
+        )
+        .await
+        .unwrap();
+
+        control_mock.assert();
+    }
+}
@@ -0,0 +1,197 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use anyhow::Result;
+use log::warn;
+use rika_firenet_client::{RikaFirenet, StoveStatus};
+use tokio::sync::{Mutex, watch};
+
+/// Status of every stove of the account, as of the last poll.
+#[derive(Clone, Debug, Default)]
+pub struct FleetSnapshot {
+    /// `None` until the first successful poll
+    pub polled_at: Option<SystemTime>,
+    /// Last known status by stove id
+    pub stoves: BTreeMap<String, StoveStatus>,
+    /// Error of the last poll by stove id, the stove keeps its previous status
+    pub failures: BTreeMap<String, String>,
+}
+
+/// Polls Firenet on behalf of every consumer so that they never call Firenet themselves.
+pub struct StovePoller {
+    firenet: Arc<dyn RikaFirenet + Send + Sync>,
+    interval: Duration,
+    snapshot: watch::Sender<Arc<FleetSnapshot>>,
+    /// Held for a whole poll, so that a poll started earlier can't replace a newer snapshot
+    polling: Mutex<()>,
+}
+
+impl StovePoller {
+    pub fn new(firenet: Arc<dyn RikaFirenet + Send + Sync>, interval: Duration) -> Self {
+        StovePoller {
+            firenet,
+            interval,
+            snapshot: watch::Sender::new(Arc::new(FleetSnapshot::default())),
+            polling: Mutex::new(()),
+        }
+    }
+
+    pub fn firenet(&self) -> &Arc<dyn RikaFirenet + Send + Sync> {
+        &self.firenet
+    }
+
+    pub fn latest(&self) -> Arc<FleetSnapshot> {
+        self.snapshot.borrow().clone()
+    }
+
+    /// The receiver sees the current snapshot first, then each new poll.
+    pub fn subscribe(&self) -> watch::Receiver<Arc<FleetSnapshot>> {
+        self.snapshot.subscribe()
+    }
+
+    /// Fetches every stove status once, a failing stove doesn't prevent the others to update.
+    ///
+    /// Concurrent polls run one after the other, e.g. a refresh after a write waits for the
+    /// background poll in progress instead of being overwritten by its older results.
+    pub async fn poll_once(&self) -> Result<()> {
+        let _polling = self.polling.lock().await;
+        let stove_ids = self.firenet.list_stoves().await?;
+        let previous = self.latest();
+        let mut snapshot = FleetSnapshot {
+            polled_at: Some(SystemTime::now()),
+            ..Default::default()
+        };
+        for stove_id in stove_ids {
+            match self.firenet.status(stove_id.clone()).await {
+                Ok(status) => {
+                    snapshot.stoves.insert(stove_id, status);
+                }
+                Err(error) => {
+                    if let Some(status) = previous.stoves.get(&stove_id) {
+                        snapshot.stoves.insert(stove_id.clone(), status.clone());
+                    }
+                    snapshot.failures.insert(stove_id, format!("{error:#}"));
+                }
+            }
+        }
+        self.snapshot.send_replace(Arc::new(snapshot));
+        Ok(())
+    }
+
+    /// Polls forever at the configured interval.
+    pub async fn run(&self) {
+        let mut ticks = tokio::time::interval(self.interval);
+        ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticks.tick().await;
+            if let Err(error) = self.poll_once().await {
+                warn!("Unable to poll stoves: {error:#}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use httpmock::{Method::GET, MockServer};
+    use rika_firenet_client::{RikaFirenetClient, StoveStatus};
+
+    use crate::poller::StovePoller;
+
+    /// The status of `../mock/src/stove-status.json`, for stove `stove_id`.
+    pub(crate) fn mock_status(stove_id: &str) -> StoveStatus {
+        serde_json::from_str(
+            &std::fs::read_to_string("../mock/src/stove-status.json")
+                .unwrap()
+                .replace("__stove_id__", stove_id),
+        )
+        .unwrap()
+    }
+
+    /// A Firenet mock serving stove 12345 and failing on stove 333444.
+    pub(crate) fn firenet_mock() -> MockServer {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/web/summary");
+            then.status(200).body_from_file("../mock/src/summary.html");
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/api/client/12345/status");
+            then.status(200)
+                .header("content-type", "application/json")
+                .body(serde_json::to_string(&mock_status("12345")).unwrap());
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/api/client/333444/status");
+            then.status(500);
+        });
+        server
+    }
+
+    pub(crate) fn poller(server: &MockServer) -> StovePoller {
+        let client = RikaFirenetClient::builder()
+            .base_url(server.base_url())
+            .build("someone@rika.com", "Secret!");
+        StovePoller::new(Arc::new(client), Duration::from_secs(60))
+    }
+
+    #[tokio::test]
+    async fn can_poll_every_stove() {
+        let server = firenet_mock();
+        let poller = poller(&server);
+        let mut receiver = poller.subscribe();
+        assert!(poller.latest().polled_at.is_none());
+
+        poller.poll_once().await.unwrap();
+
+        assert!(receiver.has_changed().unwrap());
+        let snapshot = receiver.borrow_and_update().clone();
+        assert!(snapshot.polled_at.is_some());
+        assert_eq!(snapshot.stoves.keys().collect::<Vec<_>>(), vec!["12345"]);
+        assert!(snapshot.failures.contains_key("333444"));
+    }
+
+    #[tokio::test]
+    async fn overlapping_polls_keep_the_newest_status() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/web/summary");
+            then.status(200).body_from_file("../mock/src/summary.html");
+        });
+        let status_with_name = |name: &str| {
+            let mut status = mock_status("12345");
+            status.name = name.to_string();
+            serde_json::to_string(&status).unwrap()
+        };
+        let mut before_write = server.mock(|when, then| {
+            when.method(GET).path("/api/client/12345/status");
+            then.status(200)
+                .header("content-type", "application/json")
+                .delay(Duration::from_millis(500))
+                .body(status_with_name("before write"));
+        });
+        let poller = poller(&server);
+
+        let background = poller.poll_once();
+        let refresh = async {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            before_write.delete();
+            server.mock(|when, then| {
+                when.method(GET).path("/api/client/12345/status");
+                then.status(200)
+                    .header("content-type", "application/json")
+                    .body(status_with_name("after write"));
+            });
+            poller.poll_once().await
+        };
+        let (background, refresh) = tokio::join!(background, refresh);
+        background.unwrap();
+        refresh.unwrap();
+
+        assert_eq!(poller.latest().stoves["12345"].name, "after write");
+    }
+}
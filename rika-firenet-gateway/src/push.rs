@@ -0,0 +1,376 @@
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use axum::{
+    Router,
+    extract::{
+        Query, State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    response::{
+        IntoResponse,
+        sse::{Event, KeepAlive, Sse},
+    },
+    routing::get,
+};
+use futures_util::{Stream, StreamExt, stream};
+use log::debug;
+use rika_firenet_client::{
+    HasDetailledStatus, HasStatusReadings, StoveStatus,
+    faults::{ErrorCountIncrease, FaultTable, StoveFault, increased_error_counts},
+    model::StatusDetail,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::poller::{FleetSnapshot, StovePoller};
+
+/// Change pushed to subscribers, serialized with its kind in the `type` field.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PushEvent {
+    /// Full status, sent on subscription then whenever it changes
+    Status {
+        stove_id: String,
+        status: Box<StoveStatus>,
+    },
+    DetailChanged {
+        stove_id: String,
+        from: StatusDetail,
+        to: StatusDetail,
+    },
+    /// Fault with its raw codes, the gateway doesn't describe them
+    FaultRaised {
+        stove_id: String,
+        fault: StoveFault,
+    },
+    FaultCleared {
+        stove_id: String,
+        fault: StoveFault,
+    },
+    ErrorCountIncreased {
+        stove_id: String,
+        increase: ErrorCountIncrease,
+    },
+    PollFailed {
+        stove_id: String,
+        error: String,
+    },
+}
+
+impl PushEvent {
+    /// Value of the `type` field, also used as the SSE event name.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            PushEvent::Status { .. } => "status",
+            PushEvent::DetailChanged { .. } => "detail_changed",
+            PushEvent::FaultRaised { .. } => "fault_raised",
+            PushEvent::FaultCleared { .. } => "fault_cleared",
+            PushEvent::ErrorCountIncreased { .. } => "error_count_increased",
+            PushEvent::PollFailed { .. } => "poll_failed",
+        }
+    }
+}
+
+/// Stoves a subscriber is interested in, e.g. `?stoves=12345,333444`, every stove when absent.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Subscription {
+    pub stoves: Option<String>,
+}
+
+impl Subscription {
+    pub fn accepts(&self, stove_id: &str) -> bool {
+        self.stoves
+            .as_deref()
+            .is_none_or(|stoves| stoves.split(',').any(|id| id.trim() == stove_id))
+    }
+}
+
+/// Events turning the `previous` snapshot into the `current` one, for the subscribed stoves.
+///
+/// Stoves missing from `previous` only get their status, so that a new subscription starts with
+/// the current snapshot rather than with every fault ever active.
+pub fn changes(
+    previous: &FleetSnapshot,
+    current: &FleetSnapshot,
+    subscription: &Subscription,
+) -> Vec<PushEvent> {
+    let mut events = Vec::new();
+    for (stove_id, status) in &current.stoves {
+        if !subscription.accepts(stove_id) {
+            continue;
+        }
+        let stove_id = stove_id.clone();
+        let Some(before) = previous.stoves.get(&stove_id) else {
+            events.push(PushEvent::Status {
+                stove_id,
+                status: Box::new(status.clone()),
+            });
+            continue;
+        };
+        if before == status {
+            continue;
+        }
+        let (from, to) = (before.get_status_details(), status.get_status_details());
+        if from != to {
+            events.push(PushEvent::DetailChanged {
+                stove_id: stove_id.clone(),
+                from,
+                to,
+            });
+        }
+        let table = FaultTable::default();
+        let (faults_before, faults_after) =
+            (before.active_faults(&table), status.active_faults(&table));
+        for fault in faults_after.iter().filter(|f| !faults_before.contains(f)) {
+            events.push(PushEvent::FaultRaised {
+                stove_id: stove_id.clone(),
+                fault: fault.clone(),
+            });
+        }
+        for fault in faults_before.iter().filter(|f| !faults_after.contains(f)) {
+            events.push(PushEvent::FaultCleared {
+                stove_id: stove_id.clone(),
+                fault: fault.clone(),
+            });
+        }
+        for increase in increased_error_counts(&before.error_counts(), &status.error_counts()) {
+            events.push(PushEvent::ErrorCountIncreased {
+                stove_id: stove_id.clone(),
+                increase,
+            });
+        }
+        events.push(PushEvent::Status {
+            stove_id,
+            status: Box::new(status.clone()),
+        });
+    }
+    for (stove_id, error) in &current.failures {
+        if subscription.accepts(stove_id) && previous.failures.get(stove_id) != Some(error) {
+            events.push(PushEvent::PollFailed {
+                stove_id: stove_id.clone(),
+                error: error.clone(),
+            });
+        }
+    }
+    events
+}
+
+/// Events of a new subscriber: the current snapshot first, then the changes of each poll.
+pub fn subscribe(
+    poller: &StovePoller,
+    subscription: Subscription,
+) -> impl Stream<Item = PushEvent> + Send + use<> {
+    let receiver = poller.subscribe();
+    stream::unfold(
+        (receiver, None, subscription),
+        |(mut receiver, previous, subscription)| async move {
+            if previous.is_some() && receiver.changed().await.is_err() {
+                return None;
+            }
+            let current = receiver.borrow_and_update().clone();
+            let events = changes(
+                previous.as_deref().unwrap_or(&FleetSnapshot::default()),
+                &current,
+                &subscription,
+            );
+            Some((
+                stream::iter(events),
+                (receiver, Some(current), subscription),
+            ))
+        },
+    )
+    .flatten()
+}
+
+/// Serves the events as Server-Sent Events on `/events` and over WebSocket on `/events/ws`.
+pub fn router(poller: Arc<StovePoller>) -> Router {
+    Router::new()
+        .route("/events", get(server_sent_events))
+        .route("/events/ws", get(websocket))
+        .with_state(poller)
+}
+
+async fn server_sent_events(
+    State(poller): State<Arc<StovePoller>>,
+    Query(subscription): Query<Subscription>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let events = subscribe(&poller, subscription).map(|event| {
+        let data = serde_json::to_string(&event).unwrap_or_default();
+        Ok(Event::default().event(event.kind()).data(data))
+    });
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+async fn websocket(
+    State(poller): State<Arc<StovePoller>>,
+    Query(subscription): Query<Subscription>,
+    upgrade: WebSocketUpgrade,
+) -> impl IntoResponse {
+    let events = subscribe(&poller, subscription);
+    upgrade.on_upgrade(move |socket| forward(socket, events))
+}
+
+/// Sends every event as a JSON text message until either side ends.
+async fn forward(mut socket: WebSocket, events: impl Stream<Item = PushEvent>) {
+    let mut events = std::pin::pin!(events);
+    loop {
+        tokio::select! {
+            event = events.next() => {
+                let Some(event) = event else { break };
+                let text = serde_json::to_string(&event).unwrap_or_default();
+                if socket.send(Message::Text(text.into())).await.is_err() {
+                    break;
+                }
+            }
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+    debug!("WebSocket subscriber left");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use futures_util::StreamExt;
+    use rika_firenet_client::{StoveStatus, faults::FaultSource, model::StatusDetail};
+    use serde_json::Value;
+    use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+    use crate::poller::{
+        FleetSnapshot,
+        tests::{firenet_mock, mock_status, poller},
+    };
+    use crate::push::{PushEvent, Subscription, changes, router, subscribe};
+
+    fn snapshot(statuses: &[StoveStatus]) -> FleetSnapshot {
+        FleetSnapshot {
+            stoves: statuses
+                .iter()
+                .map(|status| (status.stove_id.clone(), status.clone()))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn can_filter_stoves() {
+        assert!(Subscription::default().accepts("12345"));
+        let subscription = Subscription {
+            stoves: Some("12345, 333444".to_string()),
+        };
+        assert!(subscription.accepts("333444"));
+        assert!(!subscription.accepts("1234"));
+    }
+
+    #[test]
+    fn can_decode_changes_between_snapshots() {
+        let (before, other) = (mock_status("12345"), mock_status("333444"));
+        let mut after = before.clone();
+        after.sensors.status_sub_state = 0;
+        after.sensors.status_error = 2;
+        after.sensors.parameter_error_count2 += 1;
+        let subscription = Subscription {
+            stoves: Some("12345".to_string()),
+        };
+
+        let events = changes(
+            &snapshot(&[before.clone(), other.clone()]),
+            &snapshot(&[after.clone(), other]),
+            &subscription,
+        );
+
+        assert_eq!(events.len(), 4);
+        assert_eq!(
+            events[0],
+            PushEvent::DetailChanged {
+                stove_id: "12345".to_string(),
+                from: StatusDetail::Standby,
+                to: StatusDetail::Off,
+            }
+        );
+        assert!(matches!(
+            &events[1],
+            PushEvent::FaultRaised { fault, .. } if fault.source == FaultSource::Error && fault.code == 2
+        ));
+        assert!(matches!(
+            &events[2],
+            PushEvent::ErrorCountIncreased { increase, .. } if increase.index == 2
+        ));
+        assert_eq!(events[3].kind(), "status");
+
+        let unchanged = snapshot(std::slice::from_ref(&before));
+        assert!(changes(&unchanged, &unchanged, &subscription).is_empty());
+        let events = changes(&snapshot(&[after]), &unchanged, &subscription);
+        assert!(
+            matches!(&events[0], PushEvent::DetailChanged { to, .. } if *to == StatusDetail::Standby)
+        );
+        assert_eq!(events[1].kind(), "fault_cleared");
+    }
+
+    #[tokio::test]
+    async fn subscriptions_start_with_the_current_snapshot() {
+        let server = firenet_mock();
+        let poller = poller(&server);
+        poller.poll_once().await.unwrap();
+
+        let mut events = Box::pin(subscribe(&poller, Subscription::default()));
+
+        assert!(matches!(
+            events.next().await,
+            Some(PushEvent::Status { stove_id, .. }) if stove_id == "12345"
+        ));
+        assert!(matches!(
+            events.next().await,
+            Some(PushEvent::PollFailed { stove_id, .. }) if stove_id == "333444"
+        ));
+        poller.poll_once().await.unwrap();
+        let next = tokio::time::timeout(std::time::Duration::from_millis(100), events.next()).await;
+        assert!(next.is_err(), "nothing changed: {next:?}");
+    }
+
+    #[tokio::test]
+    async fn can_push_server_sent_events() {
+        let server = firenet_mock();
+        let poller = Arc::new(poller(&server));
+        poller.poll_once().await.unwrap();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(axum::serve(listener, router(poller)).into_future());
+
+        let mut response = reqwest::get(format!("http://{address}/events?stoves=333444"))
+            .await
+            .unwrap();
+
+        assert_eq!(response.headers()["content-type"], "text/event-stream");
+        let chunk = response.chunk().await.unwrap().unwrap();
+        let text = String::from_utf8_lossy(&chunk);
+        assert!(text.starts_with("event: poll_failed\n"), "{text}");
+        assert!(text.contains("\"stove_id\":\"333444\""), "{text}");
+    }
+
+    #[tokio::test]
+    async fn can_push_events_over_websocket() {
+        let server = firenet_mock();
+        let poller = Arc::new(poller(&server));
+        poller.poll_once().await.unwrap();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(axum::serve(listener, router(poller)).into_future());
+
+        let (mut socket, _) = connect_async(format!("ws://{address}/events/ws?stoves=12345"))
+            .await
+            .unwrap();
+
+        let Some(Ok(Message::Text(text))) = socket.next().await else {
+            panic!("expecting a text message");
+        };
+        let event: Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(event["type"], "status");
+        assert_eq!(event["stove_id"], "12345");
+        assert_eq!(event["status"]["name"], "Stove 12345");
+    }
+}
@@ -0,0 +1,652 @@
+use std::sync::Arc;
+
+use anyhow::{Result, bail, ensure};
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use log::warn;
+use rika_firenet_client::{
+    HasDetailledStatus, StoveStatus,
+    error::StoveControlError,
+    model::{DailySchedule, HeatPeriod, HeatTime, HeatingSchedule, StatusDetail, Weekday},
+};
+use rika_firenet_openapi::apis::{
+    self,
+    stoves_api::{ListStovesError, StoveControlsError, StoveStatusError},
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{OpenApi, ToSchema};
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::poller::StovePoller;
+
+type Poller = Arc<StovePoller>;
+
+#[derive(OpenApi)]
+#[openapi(info(
+    title = "Rika Firenet gateway",
+    description = "Local JSON API controlling the stoves of one Firenet account"
+))]
+struct ApiDoc;
+
+/// Serves the stoves endpoints and their OpenAPI document on `/openapi.json`.
+///
+/// Every request goes through the poller client, hence through its single Firenet session.
+/// Successful writes trigger a poll, so that other consumers see the change right away.
+pub fn router(poller: Poller) -> Router {
+    let (router, openapi) = api_router().with_state(poller).split_for_parts();
+    router.route(
+        "/openapi.json",
+        get(move || {
+            let openapi = openapi.clone();
+            async move { Json(openapi) }
+        }),
+    )
+}
+
+/// The OpenAPI document describing [`router`].
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    api_router().into_openapi()
+}
+
+fn api_router() -> OpenApiRouter<Poller> {
+    OpenApiRouter::with_openapi(ApiDoc::openapi())
+        .routes(routes!(list_stoves))
+        .routes(routes!(stove))
+        .routes(routes!(set_power))
+        .routes(routes!(set_mode))
+        .routes(routes!(set_frost_protection))
+        .routes(routes!(schedule, set_schedule, disable_schedule))
+}
+
+/// Error reported to the caller, in place of the Firenet or validation error.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorBody {
+    pub error: String,
+}
+
+/// Maps client errors to the status code telling who is at fault.
+struct ApiError(anyhow::Error);
+
+impl From<anyhow::Error> for ApiError {
+    fn from(error: anyhow::Error) -> Self {
+        ApiError(error)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let error = &self.0;
+        let status = if error.is::<StoveControlError>() {
+            StatusCode::UNPROCESSABLE_ENTITY
+        } else if error.is::<apis::Error<ListStovesError>>()
+            || error.is::<apis::Error<StoveStatusError>>()
+            || error.is::<apis::Error<StoveControlsError>>()
+        {
+            StatusCode::BAD_GATEWAY
+        } else {
+            StatusCode::BAD_REQUEST
+        };
+        let body = ErrorBody {
+            error: format!("{error:#}"),
+        };
+        (status, Json(body)).into_response()
+    }
+}
+
+type ApiResult<T> = Result<T, ApiError>;
+
+/// Polls the stoves after a successful write, a failed poll doesn't fail the write.
+async fn refreshed(poller: &StovePoller) -> ApiResult<StatusCode> {
+    if let Err(error) = poller.poll_once().await {
+        warn!("Unable to refresh stoves after a write: {error:#}");
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StoveResponse {
+    pub stove_id: String,
+    pub name: String,
+    #[schema(value_type = String, example = "Standby")]
+    pub detail: StatusDetail,
+    /// Firenet status, as received
+    #[schema(value_type = Object)]
+    pub status: StoveStatus,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PowerRequest {
+    pub on: bool,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum ModeRequest {
+    Manual {
+        heating_power: u8,
+    },
+    Auto {
+        heating_power: u8,
+    },
+    Comfort {
+        idle_temperature: u8,
+        target_temperature: u8,
+    },
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct FrostProtectionRequest {
+    /// `null` disables the frost protection
+    pub temperature: Option<u8>,
+}
+
+/// Heat periods of each day, formatted as `HH:MM-HH:MM`, at most 2 per day.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct WeekSchedule {
+    #[schema(example = json!(["06:30-08:00", "17:00-22:00"]))]
+    pub monday: Vec<String>,
+    pub tuesday: Vec<String>,
+    pub wednesday: Vec<String>,
+    pub thursday: Vec<String>,
+    pub friday: Vec<String>,
+    pub saturday: Vec<String>,
+    pub sunday: Vec<String>,
+}
+
+impl WeekSchedule {
+    pub fn day(&self, weekday: Weekday) -> &[String] {
+        match weekday {
+            Weekday::Monday => &self.monday,
+            Weekday::Tuesday => &self.tuesday,
+            Weekday::Wednesday => &self.wednesday,
+            Weekday::Thursday => &self.thursday,
+            Weekday::Friday => &self.friday,
+            Weekday::Saturday => &self.saturday,
+            Weekday::Sunday => &self.sunday,
+        }
+    }
+
+    pub fn to_heating_schedule(&self) -> Result<HeatingSchedule> {
+        let mut schedule = HeatingSchedule::all_same(DailySchedule::default());
+        for weekday in Weekday::ALL {
+            let periods = self
+                .day(weekday)
+                .iter()
+                .map(|period| parse_period(period))
+                .collect::<Result<Vec<_>>>()?;
+            ensure!(periods.len() <= 2, "{weekday} has more than 2 heat periods");
+            let mut periods = periods.into_iter();
+            *schedule.day_mut(weekday) =
+                DailySchedule::new(periods.next().unwrap_or_default(), periods.next());
+        }
+        Ok(schedule)
+    }
+}
+
+impl From<&HeatingSchedule> for WeekSchedule {
+    fn from(schedule: &HeatingSchedule) -> Self {
+        let periods = |weekday| {
+            let day = schedule.day(weekday);
+            [day.first_period(), day.second_period()]
+                .into_iter()
+                .flatten()
+                .map(ToString::to_string)
+                .collect()
+        };
+        WeekSchedule {
+            monday: periods(Weekday::Monday),
+            tuesday: periods(Weekday::Tuesday),
+            wednesday: periods(Weekday::Wednesday),
+            thursday: periods(Weekday::Thursday),
+            friday: periods(Weekday::Friday),
+            saturday: periods(Weekday::Saturday),
+            sunday: periods(Weekday::Sunday),
+        }
+    }
+}
+
+fn parse_period(period: &str) -> Result<HeatPeriod> {
+    let times = period
+        .split_once('-')
+        .and_then(|(begin, end)| Some((begin.split_once(':')?, end.split_once(':')?)));
+    let Some(((begin_hours, begin_minutes), (end_hours, end_minutes))) = times else {
+        bail!("Heat period {period} doesn't match HH:MM-HH:MM");
+    };
+    let begin = HeatTime::new(begin_hours.parse()?, begin_minutes.parse()?)?;
+    let end = HeatTime::new(end_hours.parse()?, end_minutes.parse()?)?;
+    HeatPeriod::new(begin.hours(), begin.minutes(), end.hours(), end.minutes())
+}
+
+/// Lists the ids of the stoves of the account.
+#[utoipa::path(
+    get,
+    path = "/stoves",
+    responses(
+        (status = OK, body = Vec<String>),
+        (status = BAD_GATEWAY, body = ErrorBody),
+    )
+)]
+async fn list_stoves(State(poller): State<Poller>) -> ApiResult<Json<Vec<String>>> {
+    Ok(Json(poller.firenet().list_stoves().await?))
+}
+
+/// Fetches the current status of a stove.
+#[utoipa::path(
+    get,
+    path = "/stoves/{id}",
+    params(("id" = String, Path, description = "Stove id")),
+    responses(
+        (status = OK, body = StoveResponse),
+        (status = BAD_GATEWAY, body = ErrorBody),
+    )
+)]
+async fn stove(
+    State(poller): State<Poller>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<StoveResponse>> {
+    let status = poller.firenet().status(id.clone()).await?;
+    Ok(Json(StoveResponse {
+        stove_id: id,
+        name: status.name.clone(),
+        detail: status.get_status_details(),
+        status,
+    }))
+}
+
+/// Turns a stove on or off.
+#[utoipa::path(
+    put,
+    path = "/stoves/{id}/power",
+    params(("id" = String, Path, description = "Stove id")),
+    request_body = PowerRequest,
+    responses(
+        (status = NO_CONTENT),
+        (status = BAD_REQUEST, description = "Invalid request or value out of the stove limits", body = ErrorBody),
+        (status = UNPROCESSABLE_ENTITY, description = "Operation unsupported by the stove", body = ErrorBody),
+        (status = BAD_GATEWAY, body = ErrorBody),
+    )
+)]
+async fn set_power(
+    State(poller): State<Poller>,
+    Path(id): Path<String>,
+    Json(request): Json<PowerRequest>,
+) -> ApiResult<StatusCode> {
+    let firenet = poller.firenet();
+    if request.on {
+        firenet.turn_on(id).await?;
+    } else {
+        firenet.turn_off(id).await?;
+    }
+    refreshed(&poller).await
+}
+
+/// Switches a stove to the manual, auto or comfort mode.
+#[utoipa::path(
+    put,
+    path = "/stoves/{id}/mode",
+    params(("id" = String, Path, description = "Stove id")),
+    request_body = ModeRequest,
+    responses(
+        (status = NO_CONTENT),
+        (status = BAD_REQUEST, description = "Invalid request or value out of the stove limits", body = ErrorBody),
+        (status = UNPROCESSABLE_ENTITY, description = "Operation unsupported by the stove", body = ErrorBody),
+        (status = BAD_GATEWAY, body = ErrorBody),
+    )
+)]
+async fn set_mode(
+    State(poller): State<Poller>,
+    Path(id): Path<String>,
+    Json(request): Json<ModeRequest>,
+) -> ApiResult<StatusCode> {
+    let firenet = poller.firenet();
+    match request {
+        ModeRequest::Manual { heating_power } => firenet.set_manual_mode(id, heating_power).await?,
+        ModeRequest::Auto { heating_power } => firenet.set_auto_mode(id, heating_power).await?,
+        ModeRequest::Comfort {
+            idle_temperature,
+            target_temperature,
+        } => {
+            firenet
+                .set_comfort_mode(id, idle_temperature, target_temperature)
+                .await?
+        }
+    }
+    refreshed(&poller).await
+}
+
+/// Enables or disables the frost protection of a stove.
+#[utoipa::path(
+    put,
+    path = "/stoves/{id}/frost-protection",
+    params(("id" = String, Path, description = "Stove id")),
+    request_body = FrostProtectionRequest,
+    responses(
+        (status = NO_CONTENT),
+        (status = BAD_REQUEST, description = "Invalid request or value out of the stove limits", body = ErrorBody),
+        (status = UNPROCESSABLE_ENTITY, description = "Operation unsupported by the stove", body = ErrorBody),
+        (status = BAD_GATEWAY, body = ErrorBody),
+    )
+)]
+async fn set_frost_protection(
+    State(poller): State<Poller>,
+    Path(id): Path<String>,
+    Json(request): Json<FrostProtectionRequest>,
+) -> ApiResult<StatusCode> {
+    let firenet = poller.firenet();
+    match request.temperature {
+        Some(temperature) => firenet.enable_frost_protection(id, temperature).await?,
+        None => firenet.disable_frost_protection(id).await?,
+    }
+    refreshed(&poller).await
+}
+
+/// Reads the heating schedule of a stove, whether it is active or not.
+#[utoipa::path(
+    get,
+    path = "/stoves/{id}/schedule",
+    params(("id" = String, Path, description = "Stove id")),
+    responses(
+        (status = OK, body = WeekSchedule),
+        (status = BAD_GATEWAY, body = ErrorBody),
+    )
+)]
+async fn schedule(
+    State(poller): State<Poller>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<WeekSchedule>> {
+    Ok(Json(WeekSchedule::from(
+        &poller.firenet().get_schedule(id).await?,
+    )))
+}
+
+/// Replaces the heating schedule of a stove and activates it.
+#[utoipa::path(
+    put,
+    path = "/stoves/{id}/schedule",
+    params(("id" = String, Path, description = "Stove id")),
+    request_body = WeekSchedule,
+    responses(
+        (status = NO_CONTENT),
+        (status = BAD_REQUEST, description = "Invalid request or heat periods", body = ErrorBody),
+        (status = UNPROCESSABLE_ENTITY, description = "Operation unsupported by the stove", body = ErrorBody),
+        (status = BAD_GATEWAY, body = ErrorBody),
+    )
+)]
+async fn set_schedule(
+    State(poller): State<Poller>,
+    Path(id): Path<String>,
+    Json(request): Json<WeekSchedule>,
+) -> ApiResult<StatusCode> {
+    let firenet = poller.firenet();
+    firenet
+        .enable_schedule(id, request.to_heating_schedule()?)
+        .await?;
+    refreshed(&poller).await
+}
+
+/// Deactivates the heating schedule of a stove, the heat periods are kept.
+#[utoipa::path(
+    delete,
+    path = "/stoves/{id}/schedule",
+    params(("id" = String, Path, description = "Stove id")),
+    responses(
+        (status = NO_CONTENT),
+        (status = UNPROCESSABLE_ENTITY, description = "Operation unsupported by the stove", body = ErrorBody),
+        (status = BAD_GATEWAY, body = ErrorBody),
+    )
+)]
+async fn disable_schedule(
+    State(poller): State<Poller>,
+    Path(id): Path<String>,
+) -> ApiResult<StatusCode> {
+    let firenet = poller.firenet();
+    firenet.disable_schedule(id).await?;
+    refreshed(&poller).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+
+    use httpmock::Method::POST;
+    use regex::Regex;
+    use reqwest::StatusCode;
+    use rika_firenet_client::model::Weekday;
+    use serde_json::{Value, json};
+
+    use crate::poller::{
+        StovePoller,
+        tests::{firenet_mock, poller},
+    };
+    use crate::rest::{WeekSchedule, openapi, router};
+
+    async fn serve(server: &httpmock::MockServer) -> SocketAddr {
+        serve_poller(Arc::new(poller(server))).await
+    }
+
+    async fn serve_poller(poller: Arc<StovePoller>) -> SocketAddr {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(axum::serve(listener, router(poller)).into_future());
+        address
+    }
+
+    #[tokio::test]
+    async fn can_read_stoves() {
+        let server = firenet_mock();
+        let address = serve(&server).await;
+
+        let stoves: Vec<String> = reqwest::get(format!("http://{address}/stoves"))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(stoves, vec!["12345", "333444"]);
+
+        let stove: Value = reqwest::get(format!("http://{address}/stoves/12345"))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(stove["name"], "Stove 12345");
+        assert_eq!(stove["detail"], "Standby");
+        assert_eq!(stove["status"]["sensors"]["inputRoomTemperature"], "19.6");
+
+        let response = reqwest::get(format!("http://{address}/stoves/333444"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+    }
+
+    #[tokio::test]
+    async fn can_set_comfort_mode() {
+        let server = firenet_mock();
+        let control_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/api/client/12345/controls")
+                .body_matches(Regex::new("(^|&)operatingMode=2(&|$)").unwrap())
+                .body_matches(Regex::new("(^|&)setBackTemperature=16(&|$)").unwrap())
+                .body_matches(Regex::new("(^|&)targetTemperature=21(&|$)").unwrap());
+            then.status(200).body("OK");
+        });
+        let address = serve(&server).await;
+
+        let response = reqwest::Client::new()
+            .put(format!("http://{address}/stoves/12345/mode"))
+            .json(&json!({"mode": "comfort", "idle_temperature": 16, "target_temperature": 21}))
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        control_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn can_poll_stoves_after_a_write() {
+        let server = firenet_mock();
+        server.mock(|when, then| {
+            when.method(POST).path("/api/client/12345/controls");
+            then.status(200).body("OK");
+        });
+        let poller = Arc::new(poller(&server));
+        let snapshots = poller.subscribe();
+        let address = serve_poller(poller.clone()).await;
+
+        let response = reqwest::Client::new()
+            .put(format!("http://{address}/stoves/12345/power"))
+            .json(&json!({"on": false}))
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(snapshots.has_changed().unwrap());
+        assert!(poller.latest().stoves.contains_key("12345"));
+    }
+
+    #[tokio::test]
+    async fn cant_set_values_out_of_the_stove_limits() {
+        let server = firenet_mock();
+        let control_mock = server.mock(|when, then| {
+            when.method(POST).path("/api/client/12345/controls");
+            then.status(200).body("OK");
+        });
+        let address = serve(&server).await;
+
+        let response = reqwest::Client::new()
+            .put(format!("http://{address}/stoves/12345/mode"))
+            .json(&json!({"mode": "manual", "heating_power": 150}))
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body: Value = response.json().await.unwrap();
+        assert!(body["error"].as_str().unwrap().contains("150"));
+        control_mock.assert_calls(0);
+    }
+
+    #[tokio::test]
+    async fn can_read_and_replace_the_schedule() {
+        let server = firenet_mock();
+        let control_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/api/client/12345/controls")
+                .body_matches(Regex::new("(^|&)heatingTimesActiveForComfort=true(&|$)").unwrap())
+                .body_matches(Regex::new("(^|&)heatingTimeMon1=06300800(&|$)").unwrap())
+                .body_matches(Regex::new("(^|&)heatingTimeMon2=17002200(&|$)").unwrap())
+                .body_matches(Regex::new("(^|&)heatingTimeTue1=00000000(&|$)").unwrap());
+            then.status(200).body("OK");
+        });
+        let address = serve(&server).await;
+        let http_client = reqwest::Client::new();
+
+        let schedule: WeekSchedule =
+            reqwest::get(format!("http://{address}/stoves/12345/schedule"))
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+        assert_eq!(schedule.monday, vec!["07:00-10:00", "17:00-22:00"]);
+        assert_eq!(schedule.sunday, vec!["07:00-23:00"]);
+
+        let replacement = WeekSchedule {
+            monday: vec!["06:30-08:00".to_string(), "17:00-22:00".to_string()],
+            ..Default::default()
+        };
+        let response = http_client
+            .put(format!("http://{address}/stoves/12345/schedule"))
+            .json(&replacement)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        control_mock.assert();
+
+        let invalid = WeekSchedule {
+            friday: vec!["25:00-26:00".to_string()],
+            ..Default::default()
+        };
+        let response = http_client
+            .put(format!("http://{address}/stoves/12345/schedule"))
+            .json(&invalid)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        control_mock.assert_calls(1);
+    }
+
+    #[test]
+    fn can_convert_week_schedules() {
+        let schedule = WeekSchedule {
+            tuesday: vec!["06:00-08:00".to_string(), "18:00-21:30".to_string()],
+            ..Default::default()
+        };
+
+        let heating_schedule = schedule.to_heating_schedule().unwrap();
+
+        assert_eq!(
+            heating_schedule
+                .day(Weekday::Tuesday)
+                .second_period()
+                .unwrap()
+                .to_string(),
+            "18:00-21:30"
+        );
+        assert!(
+            heating_schedule
+                .day(Weekday::Monday)
+                .first_period()
+                .is_none()
+        );
+        assert_eq!(WeekSchedule::from(&heating_schedule), schedule);
+
+        for invalid in ["6:00", "06:00-08:xx", "10:00-08:00"] {
+            let schedule = WeekSchedule {
+                monday: vec![invalid.to_string()],
+                ..Default::default()
+            };
+            assert!(schedule.to_heating_schedule().is_err(), "{invalid}");
+        }
+        let too_many = WeekSchedule {
+            monday: vec!["06:00-07:00".to_string(); 3],
+            ..Default::default()
+        };
+        assert!(too_many.to_heating_schedule().is_err());
+    }
+
+    #[tokio::test]
+    async fn can_publish_the_openapi_document() {
+        let server = firenet_mock();
+        let address = serve(&server).await;
+
+        let document: Value = reqwest::get(format!("http://{address}/openapi.json"))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+
+        assert_eq!(document, serde_json::to_value(openapi()).unwrap());
+        let paths = document["paths"].as_object().unwrap();
+        assert!(paths.contains_key("/stoves/{id}/mode"));
+        assert!(paths["/stoves/{id}/schedule"]["put"].is_object());
+        assert!(paths["/stoves/{id}/schedule"]["delete"].is_object());
+        assert!(document["components"]["schemas"]["ModeRequest"].is_object());
+        let responses = &paths["/stoves/{id}/power"]["put"]["responses"];
+        assert!(responses["400"].is_object());
+        assert!(responses["422"].is_object());
+        assert!(paths["/stoves/{id}/schedule"]["delete"]["responses"]["422"].is_object());
+    }
+}
@@ -0,0 +1,120 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use httpmock::{
+    Method::{GET, POST},
+    MockServer,
+};
+use regex::Regex;
+use rika_firenet_client::RikaFirenetClient;
+use rika_firenet_gateway::{
+    mqtt::{MqttBridge, MqttTopics},
+    poller::StovePoller,
+};
+use rumqttc::{AsyncClient, Event, MqttOptions, Packet, QoS};
+use testcontainers::{
+    ContainerAsync, GenericImage,
+    core::{ContainerPort, WaitFor},
+    runners::AsyncRunner,
+};
+
+async fn start_broker() -> ContainerAsync<GenericImage> {
+    GenericImage::new("eclipse-mosquitto", "1.6")
+        .with_exposed_port(ContainerPort::Tcp(1883))
+        .with_wait_for(WaitFor::message_on_either_std(" running"))
+        .start()
+        .await
+        .unwrap()
+}
+
+fn start_firenet_mock() -> MockServer {
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET).path("/web/summary");
+        then.status(200).body(
+            r#"<ul id="stoveList"><li><a href="/web/stove/12345">Stove n°12345</a></li></ul>"#,
+        );
+    });
+    server.mock(|when, then| {
+        when.method(GET).path("/api/client/12345/status");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body_from_file("../mock/src/stove-status.json");
+    });
+    server
+}
+
+#[tokio::test]
+async fn can_bridge_stoves_to_mqtt() {
+    let broker = start_broker().await;
+    let broker_port = broker.get_host_port_ipv4(1883).await.unwrap();
+    let firenet = start_firenet_mock();
+    let turn_off_mock = firenet.mock(|when, then| {
+        when.method(POST)
+            .path("/api/client/12345/controls")
+            .body_matches(Regex::new("(^|&)onOff=false(&|$)").unwrap());
+        then.status(200).body("OK");
+    });
+
+    let client = RikaFirenetClient::builder()
+        .base_url(firenet.base_url())
+        .build("someone@rika.com", "Secret!");
+    let poller = Arc::new(StovePoller::new(Arc::new(client), Duration::from_secs(60)));
+    poller.poll_once().await.unwrap();
+    let bridge = MqttBridge::new(poller.clone(), MqttTopics::default());
+    tokio::spawn(async move {
+        bridge
+            .run(MqttOptions::new("bridge", "127.0.0.1", broker_port))
+            .await
+    });
+
+    let (observer, mut event_loop) =
+        AsyncClient::new(MqttOptions::new("observer", "127.0.0.1", broker_port), 16);
+    observer
+        .subscribe("homeassistant/#", QoS::AtLeastOnce)
+        .await
+        .unwrap();
+    observer
+        .subscribe("rika_firenet/+/detail", QoS::AtLeastOnce)
+        .await
+        .unwrap();
+    let (mut climate_config, mut detail) = (None, None);
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while climate_config.is_none() || detail.is_none() {
+            if let Event::Incoming(Packet::Publish(publish)) = event_loop.poll().await.unwrap() {
+                let payload = String::from_utf8_lossy(&publish.payload).to_string();
+                match publish.topic.as_str() {
+                    "homeassistant/climate/rika_12345/config" => climate_config = Some(payload),
+                    "rika_firenet/12345/detail" => detail = Some(payload),
+                    _ => {}
+                }
+            }
+        }
+    })
+    .await
+    .expect("discovery and state messages");
+    assert!(
+        climate_config
+            .unwrap()
+            .contains("rika_firenet/12345/mode/set")
+    );
+    assert_eq!(detail.unwrap(), "Standby");
+
+    observer
+        .publish(
+            "rika_firenet/12345/mode/set",
+            QoS::AtLeastOnce,
+            false,
+            "off",
+        )
+        .await
+        .unwrap();
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while turn_off_mock.calls() == 0 {
+            let _ = tokio::time::timeout(Duration::from_millis(100), event_loop.poll()).await;
+        }
+    })
+    .await
+    .expect("a turn off control");
+    turn_off_mock.assert();
+}
@@ -0,0 +1,96 @@
+use rika_firenet_client::RikaFirenetClient;
+use rika_firenet_gateway::{
+    poller::StovePoller,
+    rest::{self, WeekSchedule},
+};
+use serde_json::{Value, json};
+use std::sync::Arc;
+use std::time::Duration;
+use testcontainers::{
+    ContainerAsync, GenericImage,
+    core::{ContainerPort, WaitFor},
+    runners::AsyncRunner,
+};
+
+async fn start_rika_mock() -> ContainerAsync<GenericImage> {
+    GenericImage::new("rika-firenet-api-mock", "latest")
+        .with_exposed_port(ContainerPort::Tcp(3000))
+        .with_wait_for(WaitFor::message_on_stdout(
+            "Rika Firenet mock listening on port 3000",
+        ))
+        .start()
+        .await
+        .unwrap()
+}
+
+async fn start_gateway(container: &ContainerAsync<GenericImage>) -> String {
+    let firenet_port = container.get_host_port_ipv4(3000).await.unwrap();
+    let client = RikaFirenetClient::builder()
+        .base_url(format!("http://127.0.0.1:{firenet_port}"))
+        .build("registered-user@rika-firenet.com", "Secret");
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let poller = StovePoller::new(Arc::new(client), Duration::from_secs(60));
+    tokio::spawn(axum::serve(listener, rest::router(Arc::new(poller))).into_future());
+    format!("http://{address}")
+}
+
+#[tokio::test]
+async fn can_control_a_stove_through_the_gateway() {
+    let container = start_rika_mock().await;
+    let gateway = start_gateway(&container).await;
+    let http_client = reqwest::Client::new();
+
+    let stoves: Vec<String> = reqwest::get(format!("{gateway}/stoves"))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(stoves, vec!["12345", "333444"]);
+
+    let response = http_client
+        .put(format!("{gateway}/stoves/12345/mode"))
+        .json(&json!({"mode": "manual", "heating_power": 70}))
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+
+    let stove: Value = reqwest::get(format!("{gateway}/stoves/12345"))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(stove["status"]["controls"]["operatingMode"], 0);
+    assert_eq!(stove["status"]["controls"]["heatingPower"], 70);
+}
+
+#[tokio::test]
+async fn can_replace_a_stove_schedule_through_the_gateway() {
+    let container = start_rika_mock().await;
+    let gateway = start_gateway(&container).await;
+    let schedule = WeekSchedule {
+        monday: vec!["06:30-08:00".to_string()],
+        saturday: vec!["08:00-12:00".to_string(), "18:00-23:00".to_string()],
+        ..Default::default()
+    };
+
+    let response = reqwest::Client::new()
+        .put(format!("{gateway}/stoves/12345/schedule"))
+        .json(&schedule)
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+
+    let actual: WeekSchedule = reqwest::get(format!("{gateway}/stoves/12345/schedule"))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(actual.monday, schedule.monday);
+    assert_eq!(actual.saturday, schedule.saturday);
+}
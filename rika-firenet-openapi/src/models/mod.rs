@@ -6,3 +6,5 @@ pub mod stove_sensors;
 pub use self::stove_sensors::StoveSensors;
 pub mod stove_status;
 pub use self::stove_status::StoveStatus;
+pub mod temperature;
+pub use self::temperature::Temperature;
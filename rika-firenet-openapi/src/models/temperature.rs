@@ -0,0 +1,151 @@
+//! Typed accessors for the temperatures Firenet transmits as strings.
+//!
+//! This file isn't generated, keep it listed in `.openapi-generator-ignore` along with `mod.rs`.
+
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{StoveControls, StoveSensors};
+
+/// Value sent by Firenet when a temperature isn't available, e.g. bake temperature of a stove
+/// without oven.
+pub const UNAVAILABLE_TEMPERATURE: &str = "1024";
+
+/// A temperature in degrees Celsius.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Temperature(f32);
+
+impl Temperature {
+    pub fn from_celsius(celsius: f32) -> Self {
+        Temperature(celsius)
+    }
+
+    pub fn celsius(&self) -> f32 {
+        self.0
+    }
+
+    /// Parses a Firenet temperature, `None` when missing, unparsable or equal to the
+    /// [`UNAVAILABLE_TEMPERATURE`] sentinel.
+    pub fn parse_firenet(value: &str) -> Option<Self> {
+        match value.trim() {
+            UNAVAILABLE_TEMPERATURE => None,
+            value => value.parse().ok(),
+        }
+    }
+}
+
+impl FromStr for Temperature {
+    type Err = std::num::ParseFloatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Temperature(s.trim().parse()?))
+    }
+}
+
+/// Formats the temperature the way Firenet expects it: `20`, `19.5`, `-0.5`.
+impl Display for Temperature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<f32> for Temperature {
+    fn from(celsius: f32) -> Self {
+        Temperature(celsius)
+    }
+}
+
+fn parse_optional(value: &Option<String>) -> Option<Temperature> {
+    value.as_deref().and_then(Temperature::parse_firenet)
+}
+
+impl StoveSensors {
+    pub fn input_room_temperature(&self) -> Option<Temperature> {
+        Temperature::parse_firenet(&self.input_room_temperature)
+    }
+
+    pub fn input_bake_temperature(&self) -> Option<Temperature> {
+        Temperature::parse_firenet(&self.input_bake_temperature)
+    }
+
+    pub fn input_board_temperature(&self) -> Option<Temperature> {
+        Temperature::parse_firenet(&self.input_board_temperature)
+    }
+}
+
+impl StoveControls {
+    pub fn target_temperature(&self) -> Option<Temperature> {
+        parse_optional(&self.target_temperature)
+    }
+
+    pub fn set_back_temperature(&self) -> Option<Temperature> {
+        parse_optional(&self.set_back_temperature)
+    }
+
+    pub fn frost_protection_temperature(&self) -> Option<Temperature> {
+        parse_optional(&self.frost_protection_temperature)
+    }
+
+    pub fn temperature_offset(&self) -> Option<Temperature> {
+        parse_optional(&self.temperature_offset)
+    }
+
+    pub fn bake_temperature(&self) -> Option<Temperature> {
+        parse_optional(&self.bake_temperature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::{StoveControls, StoveSensors, Temperature};
+
+    #[test]
+    fn can_read_typed_temperatures() {
+        let sensors = StoveSensors {
+            input_room_temperature: "19.6".to_string(),
+            input_bake_temperature: "1024".to_string(),
+            input_board_temperature: "".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            sensors.input_room_temperature(),
+            Some(Temperature::from_celsius(19.6))
+        );
+        assert_eq!(sensors.input_bake_temperature(), None);
+        assert_eq!(sensors.input_board_temperature(), None);
+
+        let controls = StoveControls {
+            target_temperature: Some("20".to_string()),
+            temperature_offset: Some("-1.5".to_string()),
+            bake_temperature: Some("1024".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            controls.target_temperature(),
+            Some(Temperature::from_celsius(20.0))
+        );
+        assert_eq!(
+            controls.temperature_offset(),
+            Some(Temperature::from_celsius(-1.5))
+        );
+        assert_eq!(controls.bake_temperature(), None);
+        assert_eq!(controls.set_back_temperature(), None);
+    }
+
+    #[test]
+    fn keeps_wire_format_unchanged() {
+        let json = r#"{"targetTemperature":"20","temperatureOffset":"0.6"}"#;
+        let controls: StoveControls = serde_json::from_str(json).unwrap();
+        assert_eq!(serde_json::to_string(&controls).unwrap(), json);
+    }
+
+    #[test]
+    fn can_format_temperatures_for_firenet() {
+        assert_eq!(Temperature::from_celsius(20.0).to_string(), "20");
+        assert_eq!(Temperature::from_celsius(19.5).to_string(), "19.5");
+        assert_eq!(Temperature::from_celsius(-0.5).to_string(), "-0.5");
+    }
+}